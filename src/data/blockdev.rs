@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub const SYS_BLOCK: &str = "/sys/block";
+pub const SYS_CLASS_BLOCK: &str = "/sys/class/block";
+pub const MDSTAT: &str = "/proc/mdstat";
+pub const MOUNTS: &str = "/proc/mounts";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockKind {
+    #[default]
+    Disk,
+    Partition,
+    Lvm,
+    Crypt,
+    DeviceMapper,
+    Raid,
+    Loop,
+}
+
+impl BlockKind {
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BlockKind::Disk => "disk",
+            BlockKind::Partition => "partition",
+            BlockKind::Lvm => "lvm",
+            BlockKind::Crypt => "crypt",
+            BlockKind::DeviceMapper => "dm",
+            BlockKind::Raid => "raid",
+            BlockKind::Loop => "loop",
+        }
+    }
+}
+
+/// One md array as listed in `/proc/mdstat`, e.g.
+/// `md0 : active raid1 sdb1[1] sda1[0]` followed by `... [2/1] [U_]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MdArray {
+    pub name: String,
+    pub state: String,
+    pub level: String,
+    pub members: Vec<String>,
+    /// The member map, `U` for an up and `_` for a missing device.
+    pub status: String,
+    /// A running resync, recovery, reshape or check, e.g. `recovery = 12.6%`.
+    pub action: Option<String>,
+}
+
+impl MdArray {
+
+    pub fn is_degraded(&self) -> bool {
+        self.status.contains('_')
+    }
+}
+
+/// A node of the block device tree: a disk, its partitions and every
+/// device-mapper or md device stacked on top of it, with where it is mounted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockDevice {
+    pub name: String,
+    pub kind: BlockKind,
+    pub size: u64,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub rotational: bool,
+    pub read_only: bool,
+    pub scheduler: Option<String>,
+    /// The device-mapper name, e.g. `vg0-root` or `luks-<uuid>`.
+    pub dm_name: Option<String>,
+    pub raid: Option<MdArray>,
+    /// The devices it is built on, from `slaves/`.
+    pub slaves: Vec<String>,
+    pub mounts: Vec<(String, String)>,
+    pub children: Vec<BlockDevice>,
+}
+
+impl BlockDevice {
+
+    /// Reads `/sys/class/block/<name>` and everything built on top of it:
+    /// its partitions and the devices listing it in `holders/`. A holder
+    /// built on several devices, like an md array, has a tree of its own
+    /// instead of one copy under each of them.
+    fn read(name: &str, context: &Context) -> BlockDevice {
+        let dir = context.class_block.join(name);
+        let read = |file: &str| fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_owned()).filter(|s| !s.is_empty());
+        let dm_uuid = read("dm/uuid").unwrap_or_default();
+        let kind = if dir.join("partition").exists() {
+            BlockKind::Partition
+        } else if dir.join("md").exists() {
+            BlockKind::Raid
+        } else if dir.join("dm").exists() {
+            match dm_uuid.split('-').next() {
+                Some("LVM") => BlockKind::Lvm,
+                Some("CRYPT") => BlockKind::Crypt,
+                _ => BlockKind::DeviceMapper,
+            }
+        } else if name.starts_with("loop") {
+            BlockKind::Loop
+        } else {
+            BlockKind::Disk
+        };
+
+        let mut children: Vec<BlockDevice> = list_dir(&dir)
+            .into_iter()
+            .filter(|child| child.starts_with(name) && dir.join(child).join("partition").exists())
+            .chain(list_dir(&dir.join("holders")).into_iter().filter(|holder| context.slaves(holder).len() < 2))
+            .map(|child| BlockDevice::read(&child, context))
+            .collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let dm_name = read("dm/name");
+        let mounts = [format!("/dev/{}", name)].into_iter()
+            .chain(dm_name.iter().map(|n| format!("/dev/mapper/{}", n)))
+            .flat_map(|device| context.mounts.get(&device).cloned().unwrap_or_default())
+            .collect();
+        BlockDevice {
+            name: name.to_owned(),
+            size: read("size").and_then(|s| s.parse::<u64>().ok()).unwrap_or(0) * 512,
+            model: read("device/model"),
+            serial: read("device/serial").or_else(|| read("serial")).or_else(|| read("device/wwid")),
+            rotational: read("queue/rotational").as_deref() == Some("1"),
+            read_only: read("ro").as_deref() == Some("1"),
+            scheduler: read("queue/scheduler").and_then(|s| active_scheduler(&s)),
+            raid: context.arrays.iter().find(|a| a.name == name).cloned(),
+            slaves: context.slaves(name),
+            kind,
+            dm_name,
+            mounts,
+            children,
+        }
+    }
+
+    /// Calls `f` for this node and every descendant with its depth.
+    pub fn walk<'a>(&'a self, depth: usize, f: &mut impl FnMut(&'a BlockDevice, usize)) {
+        f(self, depth);
+        for child in &self.children {
+            child.walk(depth + 1, f);
+        }
+    }
+}
+
+/// What the tree walk looks up for every node.
+struct Context {
+    /// `/sys/class/block` under the root being read.
+    class_block: PathBuf,
+    /// Mount points and filesystem types by device path.
+    mounts: HashMap<String, Vec<(String, String)>>,
+    arrays: Vec<MdArray>,
+}
+
+impl Context {
+
+    fn slaves(&self, name: &str) -> Vec<String> {
+        let mut slaves = list_dir(&self.class_block.join(name).join("slaves"));
+        slaves.sort();
+        slaves
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockData {
+    pub disks: Vec<BlockDevice>,
+    pub arrays: Vec<MdArray>,
+}
+
+impl BlockData {
+
+    pub fn new() -> BlockData {
+        BlockData::from_root(Path::new("/"))
+    }
+
+    /// Builds one tree per device of `/sys/block` under `root` that sits on
+    /// no other device (its `slaves/` is empty) or on several, skipping empty
+    /// loop and zram devices. Disks come first, then arrays and loop devices.
+    pub fn from_root(root: &Path) -> BlockData {
+        let under_root = |path: &str| root.join(path.trim_start_matches('/'));
+        let context = Context {
+            class_block: under_root(SYS_CLASS_BLOCK),
+            mounts: read_mounts(&under_root(MOUNTS)),
+            arrays: parse_mdstat(&fs::read_to_string(under_root(MDSTAT)).unwrap_or_default()),
+        };
+        let mut disks: Vec<BlockDevice> = list_dir(&under_root(SYS_BLOCK))
+            .into_iter()
+            .filter(|name| context.slaves(name).len() != 1)
+            .map(|name| BlockDevice::read(&name, &context))
+            .filter(|disk| disk.size > 0)
+            .collect();
+        disks.sort_by_key(|disk| (disk.kind == BlockKind::Loop, !disk.slaves.is_empty(), disk.name.clone()));
+        BlockData { disks, arrays: context.arrays }
+    }
+
+    pub fn degraded_arrays(&self) -> impl Iterator<Item = &MdArray> {
+        self.arrays.iter().filter(|a| a.is_degraded())
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Block devices***");
+        for disk in &self.disks {
+            disk.walk(0, &mut |dev, depth| {
+                println!("{}{}............{} {} bytes {:?}", "  ".repeat(depth), dev.name, dev.kind.label(), dev.size, dev.mounts);
+            });
+        }
+        for array in &self.arrays {
+            println!("{}............{} {} {}", array.name, array.level, array.state, array.status);
+        }
+    }
+}
+
+pub fn parse_mdstat(text: &str) -> Vec<MdArray> {
+    let mut arrays: Vec<MdArray> = Vec::new();
+    for line in text.lines() {
+        if let Some((name, rest)) = line.split_once(" : ") {
+            if !name.starts_with("md") {
+                continue;
+            }
+            let mut fields = rest.split_whitespace();
+            let state = fields.next().unwrap_or_default().to_owned();
+            // `active (auto-read-only) raid1 ...`
+            let mut fields = fields.skip_while(|f| f.starts_with('('));
+            arrays.push(MdArray {
+                name: name.trim().to_owned(),
+                state,
+                level: fields.next().unwrap_or_default().to_owned(),
+                members: fields.map(|f| f.split('[').next().unwrap_or(f).to_owned()).collect(),
+                ..MdArray::default()
+            });
+        } else if let Some(array) = arrays.last_mut() {
+            let line = line.trim();
+            if array.status.is_empty() {
+                if let Some(status) = line.split_whitespace().last().filter(|s| s.starts_with('[') && s.contains(['U', '_'])) {
+                    array.status = status.trim_matches(['[', ']']).to_owned();
+                }
+            }
+            for action in ["resync", "recovery", "reshape", "check"] {
+                if let Some(at) = line.find(&format!("{} =", action)) {
+                    array.action = Some(line[at..].split_whitespace().take(3).collect::<Vec<_>>().join(" "));
+                }
+            }
+        }
+    }
+    arrays
+}
+
+/// The mounts of `/proc/mounts` by device; `/dev/disk/by-*` and
+/// `/dev/mapper` links are resolved to the kernel name as well.
+fn read_mounts(path: &Path) -> HashMap<String, Vec<(String, String)>> {
+    let mut mounts: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for line in fs::read_to_string(path).unwrap_or_default().lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 || !fields[0].starts_with("/dev/") {
+            continue;
+        }
+        let mount = (fields[1].replace("\\040", " "), fields[2].to_owned());
+        let device = fs::canonicalize(fields[0]).map(|p| p.to_string_lossy().into_owned()).unwrap_or(fields[0].to_owned());
+        mounts.entry(device).or_default().push(mount);
+    }
+    mounts
+}
+
+/// `none [mq-deadline] kyber` → `mq-deadline`.
+fn active_scheduler(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find(|s| s.starts_with('['))
+        .map(|s| s.trim_matches(['[', ']']).to_owned())
+}
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    const MDSTAT: &str = "Personalities : [raid1] [raid6] [raid5] [raid4] [linear]
+md1 : active raid1 sdb2[1] sda2[0]
+      976630464 blocks super 1.2 [2/2] [UU]
+      bitmap: 2/8 pages [8KB], 65536KB chunk
+
+md0 : active (auto-read-only) raid5 sdd1[3] sdc1[1] sde1[0](F)
+      1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [UU_]
+      [==>..................]  recovery = 12.6% (123456/976630272) finish=120.5min speed=100000K/sec
+
+unused devices: <none>
+";
+
+    #[test]
+    fn parses_mdstat() {
+        let arrays = parse_mdstat(MDSTAT);
+        assert_eq!(arrays.len(), 2);
+
+        let md1 = &arrays[0];
+        assert_eq!(md1.name, "md1");
+        assert_eq!(md1.state, "active");
+        assert_eq!(md1.level, "raid1");
+        assert_eq!(md1.members, ["sdb2", "sda2"]);
+        assert_eq!(md1.status, "UU");
+        assert_eq!(md1.action, None);
+        assert!(!md1.is_degraded());
+
+        let md0 = &arrays[1];
+        assert_eq!(md0.name, "md0");
+        assert_eq!(md0.level, "raid5");
+        assert_eq!(md0.members, ["sdd1", "sdc1", "sde1"]);
+        assert_eq!(md0.status, "UU_");
+        assert_eq!(md0.action.as_deref(), Some("recovery = 12.6%"));
+        assert!(md0.is_degraded());
+    }
+
+    #[test]
+    fn parses_mdstat_without_arrays() {
+        assert!(parse_mdstat("Personalities :\nunused devices: <none>\n").is_empty());
+    }
+
+    /// Writes `/sys/class/block/<name>` with its size in sectors: a whole
+    /// device is linked from `/sys/block`, a `disk/partition` lives in the
+    /// directory of its disk.
+    fn device(fixture: &Fixture, name: &str, sectors: u64) {
+        match name.split_once('/') {
+            Some((disk, partition)) => {
+                fixture.write(&format!("sys/class/block/{}/{}/size", disk, partition), &format!("{}\n", sectors));
+                fixture.write(&format!("sys/class/block/{}/{}/partition", disk, partition), "1\n");
+                fixture.link(&format!("sys/class/block/{}", partition), &format!("{}/{}", disk, partition));
+            }
+            None => {
+                fixture.write(&format!("sys/class/block/{}/size", name), &format!("{}\n", sectors));
+                fixture.link(&format!("sys/block/{}", name), &format!("../class/block/{}", name));
+            }
+        }
+    }
+
+    /// Stacks `holder` on `slave`, as the kernel links them both ways.
+    fn hold(fixture: &Fixture, slave: &str, holder: &str) {
+        fixture.link(&format!("sys/class/block/{}/holders/{}", slave, holder), &format!("../../{}", holder));
+        fixture.link(&format!("sys/class/block/{}/slaves/{}", holder, slave), &format!("../../{}", slave));
+    }
+
+    #[test]
+    fn builds_the_device_tree() {
+        let fixture = Fixture::new("blockdev");
+        // sda: a /boot partition and LVM inside LUKS on the other one
+        device(&fixture, "sda", 1000);
+        fixture.write("sys/class/block/sda/device/model", "Samsung SSD\n");
+        fixture.write("sys/class/block/sda/queue/rotational", "0\n");
+        fixture.write("sys/class/block/sda/queue/scheduler", "none [mq-deadline] kyber\n");
+        device(&fixture, "sda/sda1", 100);
+        device(&fixture, "sda/sda2", 900);
+        device(&fixture, "dm-0", 890);
+        fixture.write("sys/class/block/dm-0/dm/uuid", "CRYPT-LUKS2-0123-luks-0123\n");
+        fixture.write("sys/class/block/dm-0/dm/name", "luks-0123\n");
+        hold(&fixture, "sda2", "dm-0");
+        device(&fixture, "dm-1", 800);
+        fixture.write("sys/class/block/dm-1/dm/uuid", "LVM-abcdef\n");
+        fixture.write("sys/class/block/dm-1/dm/name", "vg0-root\n");
+        hold(&fixture, "dm-0", "dm-1");
+        // md0: a RAID1 of sdb1 and sdc1
+        for disk in ["sdb", "sdc"] {
+            device(&fixture, disk, 500);
+            device(&fixture, &format!("{}/{}1", disk, disk), 500);
+            hold(&fixture, &format!("{}1", disk), "md0");
+        }
+        device(&fixture, "md0", 490);
+        fixture.write("sys/class/block/md0/md/level", "raid1\n");
+        device(&fixture, "loop0", 0);
+        fixture.write("proc/mdstat", "md0 : active raid1 sdc1[1] sdb1[0]\n      245 blocks super 1.2 [2/2] [UU]\n");
+        fixture.write("proc/mounts", "/dev/sda1 /boot ext4 rw 0 0\n/dev/mapper/vg0-root / ext4 rw 0 0\n/dev/md0 /srv xfs rw 0 0\n");
+
+        let data = BlockData::from_root(fixture.path());
+        let names: Vec<&str> = data.disks.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["sda", "sdb", "sdc", "md0"]);
+
+        let sda = &data.disks[0];
+        assert_eq!((sda.kind.clone(), sda.size), (BlockKind::Disk, 1000 * 512));
+        assert_eq!(sda.model.as_deref(), Some("Samsung SSD"));
+        assert_eq!(sda.scheduler.as_deref(), Some("mq-deadline"));
+        let mut tree: Vec<(usize, String, BlockKind)> = Vec::new();
+        sda.walk(0, &mut |dev, depth| tree.push((depth, dev.name.clone(), dev.kind.clone())));
+        assert_eq!(tree, [
+            (0, "sda".to_owned(), BlockKind::Disk),
+            (1, "sda1".to_owned(), BlockKind::Partition),
+            (1, "sda2".to_owned(), BlockKind::Partition),
+            (2, "dm-0".to_owned(), BlockKind::Crypt),
+            (3, "dm-1".to_owned(), BlockKind::Lvm),
+        ]);
+        assert_eq!(sda.children[0].mounts, [("/boot".to_owned(), "ext4".to_owned())]);
+        let root = &sda.children[1].children[0].children[0];
+        assert_eq!(root.dm_name.as_deref(), Some("vg0-root"));
+        assert_eq!(root.mounts, [("/".to_owned(), "ext4".to_owned())]);
+
+        // the array is listed once, on its own, not under each member
+        for member in &data.disks[1..3] {
+            assert_eq!(member.children.len(), 1);
+            assert_eq!(member.children[0].kind, BlockKind::Partition);
+            assert!(member.children[0].children.is_empty());
+        }
+        let md0 = &data.disks[3];
+        assert_eq!(md0.kind, BlockKind::Raid);
+        assert_eq!(md0.slaves, ["sdb1", "sdc1"]);
+        assert_eq!(md0.raid.as_ref().map(|r| r.members.clone()), Some(vec!["sdc1".to_owned(), "sdb1".to_owned()]));
+        assert_eq!(md0.mounts, [("/srv".to_owned(), "xfs".to_owned())]);
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::pressure::PressureData;
+
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// Where systemd mounts the v2 hierarchy in the hybrid v1/v2 layout.
+pub const CGROUP_HYBRID_ROOT: &str = "/sys/fs/cgroup/unified";
+
+/// The resource usage of one cgroup v2 directory and of its children.
+/// Limits that are unset (`max`) or files the kernel doesn't provide for
+/// this cgroup (e.g. `memory.current` on the root) are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CgroupNode {
+    pub path: String,
+    pub name: String,
+    pub cpu_usage_usec: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<u64>,
+    pub memory_high: Option<u64>,
+    pub swap_current: Option<u64>,
+    pub io_rbytes: u64,
+    pub io_wbytes: u64,
+    pub pids_current: Option<u64>,
+    pub pressure: PressureData,
+    pub children: Vec<CgroupNode>,
+}
+
+impl CgroupNode {
+
+    /// Reads the cgroup at `root`/`path` and walks its subdirectories.
+    pub fn read(root: &Path, path: &str) -> CgroupNode {
+        let dir = root.join(path.trim_start_matches('/'));
+        let mut node = CgroupNode {
+            path: path.to_owned(),
+            name: dir.file_name().and_then(|n| n.to_str()).filter(|_| path != "/").unwrap_or("/").to_owned(),
+            memory_current: read_value(&dir.join("memory.current")),
+            memory_max: read_value(&dir.join("memory.max")),
+            memory_high: read_value(&dir.join("memory.high")),
+            swap_current: read_value(&dir.join("memory.swap.current")),
+            pids_current: read_value(&dir.join("pids.current")),
+            pressure: PressureData::read(&dir, ".pressure"),
+            ..CgroupNode::default()
+        };
+
+        for (key, value) in read_keyed(&dir.join("cpu.stat")) {
+            match key.as_str() {
+                "usage_usec" => node.cpu_usage_usec = value,
+                "nr_throttled" => node.nr_throttled = value,
+                "throttled_usec" => node.throttled_usec = value,
+                _ => {}
+            }
+        }
+
+        // io.stat has one line per device: `8:0 rbytes=1 wbytes=2 rios=3 ...`
+        for line in fs::read_to_string(dir.join("io.stat")).unwrap_or_default().lines() {
+            for field in line.split_whitespace().skip(1) {
+                match field.split_once('=') {
+                    Some(("rbytes", v)) => node.io_rbytes += v.parse::<u64>().unwrap_or(0),
+                    Some(("wbytes", v)) => node.io_wbytes += v.parse::<u64>().unwrap_or(0),
+                    _ => {}
+                }
+            }
+        }
+
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let child = path.trim_end_matches('/').to_owned() + "/" + &name;
+                    node.children.push(CgroupNode::read(root, &child));
+                }
+            }
+        }
+        node.children.sort_by(|a, b| a.name.cmp(&b.name));
+        node
+    }
+
+    /// Calls `f` for this node and every descendant.
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a CgroupNode)) {
+        f(self);
+        for child in &self.children {
+            child.walk(f);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CgroupData {
+    pub root: Option<CgroupNode>,
+}
+
+impl CgroupData {
+
+    pub fn new() -> CgroupData {
+        let data = CgroupData::from_root(Path::new(CGROUP_ROOT));
+        if data.root.is_some() {
+            data
+        } else {
+            CgroupData::from_root(Path::new(CGROUP_HYBRID_ROOT))
+        }
+    }
+
+    /// Reads the cgroup v2 hierarchy mounted at `root`; any directory with a
+    /// `cgroup.controllers` file works, so a fixture tree can stand in for
+    /// `/sys/fs/cgroup`. `root` is `None` when there is no v2 hierarchy.
+    pub fn from_root(root: &Path) -> CgroupData {
+        if !root.join("cgroup.controllers").exists() {
+            return CgroupData { root: None };
+        }
+        CgroupData {
+            root: Some(CgroupNode::read(root, "/"))
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Cgroup data***");
+        if let Some(root) = &self.root {
+            root.walk(&mut |node| {
+                println!("{}............cpu {} us, memory {:?}, pids {:?}",
+                         node.path, node.cpu_usage_usec, node.memory_current, node.pids_current);
+            });
+        }
+    }
+}
+
+/// Reads a single-value file; `max` and missing files give `None`.
+fn read_value(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok().and_then(|s| s.trim().parse::<u64>().ok())
+}
+
+/// Reads a flat keyed file made of `key value` lines.
+fn read_keyed(path: &Path) -> Vec<(String, u64)> {
+    fs::read_to_string(path).unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_owned(), value.trim().parse::<u64>().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    #[test]
+    fn reads_a_fixture_tree() {
+        let fixture = Fixture::new("cgroup");
+        fixture.write("cgroup.controllers", "cpu io memory pids\n");
+        fixture.write("cpu.stat", "usage_usec 5000\nuser_usec 3000\nsystem_usec 2000\n");
+        fixture.write("cpu.pressure", "some avg10=1.50 avg60=0.75 avg300=0.25 total=12345\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=0\n");
+        fixture.write("system.slice/cgroup.controllers", "cpu io memory pids\n");
+        fixture.write("system.slice/cpu.stat", "usage_usec 1200\nuser_usec 1000\nsystem_usec 200\nnr_periods 10\nnr_throttled 2\nthrottled_usec 345\n");
+        fixture.write("system.slice/memory.current", "1048576\n");
+        fixture.write("system.slice/memory.max", "max\n");
+        fixture.write("system.slice/memory.high", "2097152\n");
+        fixture.write("system.slice/memory.swap.current", "4096\n");
+        fixture.write("system.slice/io.stat", "8:0 rbytes=100 wbytes=200 rios=1 wios=2 dbytes=0 dios=0\n8:16 rbytes=10 wbytes=20 rios=1 wios=1 dbytes=0 dios=0\n");
+        fixture.write("system.slice/pids.current", "7\n");
+
+        let root = CgroupData::from_root(fixture.path()).root.expect("a v2 hierarchy");
+        assert_eq!(root.path, "/");
+        assert_eq!(root.name, "/");
+        assert_eq!(root.cpu_usage_usec, 5000);
+        assert_eq!(root.memory_current, None);
+        let cpu = root.pressure.cpu.as_ref().expect("cpu.pressure");
+        assert_eq!(cpu.some.avg10, 1.5);
+        assert_eq!(cpu.some.total, 12345);
+        assert!(root.pressure.memory.is_none());
+
+        assert_eq!(root.children.len(), 1);
+        let child = &root.children[0];
+        assert_eq!(child.path, "/system.slice");
+        assert_eq!(child.name, "system.slice");
+        assert_eq!(child.cpu_usage_usec, 1200);
+        assert_eq!(child.nr_throttled, 2);
+        assert_eq!(child.throttled_usec, 345);
+        assert_eq!(child.memory_current, Some(1048576));
+        assert_eq!(child.memory_max, None);
+        assert_eq!(child.memory_high, Some(2097152));
+        assert_eq!(child.swap_current, Some(4096));
+        assert_eq!(child.io_rbytes, 110);
+        assert_eq!(child.io_wbytes, 220);
+        assert_eq!(child.pids_current, Some(7));
+
+        let mut paths: Vec<&str> = Vec::new();
+        root.walk(&mut |node| paths.push(&node.path));
+        assert_eq!(paths, ["/", "/system.slice"]);
+    }
+
+    #[test]
+    fn no_hierarchy_without_controllers() {
+        let fixture = Fixture::new("cgroup-v1");
+        fixture.write("cpu/cpu.stat", "usage_usec 1\n");
+        assert_eq!(CgroupData::from_root(fixture.path()).root, None);
+    }
+}
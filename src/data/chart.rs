@@ -0,0 +1,74 @@
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke};
+use iced::{Color, Element, Fill, Length, Point, Rectangle, Renderer, Theme};
+
+/// One line of a chart; `values` are oldest first.
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub color: Color,
+    pub values: Vec<f32>,
+}
+
+/// A line chart of the recent history of one or more values, scaled from 0
+/// to `max` (or to the largest value when `max` is `None`).
+#[derive(Debug, Clone)]
+pub struct LineChart {
+    series: Vec<Series>,
+    max: Option<f32>,
+    capacity: usize,
+}
+
+impl LineChart {
+
+    /// `capacity` is the number of points the width stands for, so charts
+    /// don't stretch while the history fills up.
+    pub fn new(series: Vec<Series>, max: Option<f32>, capacity: usize) -> LineChart {
+        LineChart { series, max, capacity }
+    }
+
+    pub fn view<'a, Message: 'a>(self, height: impl Into<Length>) -> Element<'a, Message> {
+        canvas::Canvas::new(self).width(Fill).height(height).into()
+    }
+}
+
+impl<Message> canvas::Program<Message> for LineChart {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, theme: &Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), palette.background.base.color);
+        frame.stroke(
+            &Path::rectangle(Point::ORIGIN, bounds.size()),
+            Stroke::default().with_color(palette.background.strong.color).with_width(1.0),
+        );
+
+        let max = self.max.unwrap_or_else(|| {
+            self.series.iter().flat_map(|s| s.values.iter().copied()).fold(0.0, f32::max)
+        });
+        if max <= 0.0 || self.capacity < 2 {
+            return vec![frame.into_geometry()];
+        }
+        let step = bounds.width / (self.capacity - 1) as f32;
+        for series in &self.series {
+            let skip = series.values.len().saturating_sub(self.capacity);
+            let values = &series.values[skip..];
+            if values.len() < 2 {
+                continue;
+            }
+            let offset = (self.capacity - values.len()) as f32 * step;
+            let line = Path::new(|path| {
+                for (i, value) in values.iter().enumerate() {
+                    let point = Point::new(offset + i as f32 * step, bounds.height * (1.0 - (value / max).clamp(0.0, 1.0)));
+                    if i == 0 {
+                        path.move_to(point);
+                    } else {
+                        path.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(&line, Stroke::default().with_color(series.color).with_width(1.5));
+        }
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(unix)]
+use std::ffi::CString;
+#[cfg(unix)]
+use std::mem::MaybeUninit;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// What `statvfs(3)` reports for a mounted filesystem. Sizes are in bytes;
+/// `reserved_bytes` are the free blocks only root may use.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FsStats {
+    pub block_size: u64,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub available_bytes: u64,
+    pub reserved_bytes: u64,
+    pub total_inodes: u64,
+    pub free_inodes: u64,
+    pub available_inodes: u64,
+    pub max_name_length: u64,
+}
+
+impl FsStats {
+
+    /// Calls `statvfs` on `mount_point`; `None` if the call fails.
+    #[cfg(unix)]
+    pub fn read(mount_point: &Path) -> Option<FsStats> {
+        let path = CString::new(mount_point.as_os_str().as_bytes()).ok()?;
+        let mut raw = MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `path` is NUL-terminated and `raw` is only read after the
+        // call succeeded and filled it in.
+        let raw = unsafe {
+            if libc::statvfs(path.as_ptr(), raw.as_mut_ptr()) != 0 {
+                return None;
+            }
+            raw.assume_init()
+        };
+        // Block counts are in units of the fragment size, not `f_bsize`.
+        let unit = if raw.f_frsize > 0 { raw.f_frsize as u64 } else { raw.f_bsize as u64 };
+        Some(FsStats {
+            block_size: raw.f_bsize as u64,
+            total_bytes: raw.f_blocks as u64 * unit,
+            free_bytes: raw.f_bfree as u64 * unit,
+            available_bytes: raw.f_bavail as u64 * unit,
+            reserved_bytes: (raw.f_bfree as u64).saturating_sub(raw.f_bavail as u64) * unit,
+            total_inodes: raw.f_files as u64,
+            free_inodes: raw.f_ffree as u64,
+            available_inodes: raw.f_favail as u64,
+            max_name_length: raw.f_namemax as u64,
+        })
+    }
+
+    /// There's no `statvfs` outside Unix.
+    #[cfg(not(unix))]
+    pub fn read(_mount_point: &Path) -> Option<FsStats> {
+        None
+    }
+
+    pub fn used_inodes(&self) -> u64 {
+        self.total_inodes.saturating_sub(self.free_inodes)
+    }
+
+    /// Inode usage in %, or `None` for filesystems without a fixed inode
+    /// table (btrfs, vfat, ...) which report zero inodes.
+    pub fn inode_percent(&self) -> Option<f32> {
+        if self.total_inodes == 0 {
+            None
+        } else {
+            Some(self.used_inodes() as f32 * 100.0 / self.total_inodes as f32)
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Scratch directory trees standing in for /proc and /sys in tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory under the system temp dir, removed on drop.
+pub struct Fixture(PathBuf);
+
+impl Fixture {
+
+    pub fn new(name: &str) -> Fixture {
+        let dir = std::env::temp_dir().join(format!("monitoring_system-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Fixture(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn write(&self, path: &str, text: &str) {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    /// Makes `path` a symlink to `target`, like the driver, master and
+    /// lower device links of sysfs.
+    #[cfg(unix)]
+    pub fn link(&self, path: &str, target: &str) {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(target, path).unwrap();
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
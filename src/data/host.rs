@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+use super::record::Sample;
+use super::structs::AllData;
+use super::summary::Summary;
+
+/// How many samples of every host are kept for rates and charts.
+pub const HISTORY_LEN: usize = 150;
+
+static NO_DATA: LazyLock<AllData> = LazyLock::new(AllData::default);
+
+/// A machine whose data the panes can show: the local one (or the replayed
+/// recording) always comes first, then one per `--connect` agent.
+#[derive(Debug, Clone, Default)]
+pub struct Host {
+    pub name: String,
+    pub address: Option<String>,
+    pub connected: bool,
+    pub error: Option<String>,
+    pub history: VecDeque<Sample>,
+}
+
+impl Host {
+
+    pub fn local(name: &str) -> Host {
+        Host {
+            name: name.to_owned(),
+            connected: true,
+            ..Host::default()
+        }
+    }
+
+    pub fn remote(address: &str) -> Host {
+        Host {
+            name: address.to_owned(),
+            address: Some(address.to_owned()),
+            ..Host::default()
+        }
+    }
+
+    pub fn push(&mut self, sample: Sample) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(sample);
+    }
+
+    /// Replaces the history, e.g. after a jump in a replayed recording.
+    pub fn reset(&mut self, samples: impl IntoIterator<Item = Sample>) {
+        self.history.clear();
+        for sample in samples {
+            self.push(sample);
+        }
+    }
+
+    pub fn latest(&self) -> Option<&Sample> {
+        self.history.back()
+    }
+
+    pub fn previous(&self) -> Option<&Sample> {
+        self.history.len().checked_sub(2).and_then(|i| self.history.get(i))
+    }
+
+    /// The latest data, or empty data until the first sample arrives.
+    pub fn data(&self) -> &AllData {
+        self.latest().map(|s| &s.data).unwrap_or(&NO_DATA)
+    }
+
+    pub fn summary(&self) -> Option<Summary> {
+        self.latest().map(|current| Summary::new(self.previous(), current))
+    }
+
+    /// The summary of every sample but the first, oldest first, for charts.
+    pub fn summaries(&self) -> impl Iterator<Item = Summary> + '_ {
+        self.history.iter().zip(self.history.iter().skip(1)).map(|(prev, cur)| Summary::new(Some(prev), cur))
+    }
+
+    pub fn status(&self) -> String {
+        match (&self.address, self.connected, &self.error) {
+            (None, _, _) => self.name.clone(),
+            (Some(address), true, _) => self.name.clone() + " (" + address + ")",
+            (Some(address), false, Some(e)) => self.name.clone() + " (" + address + ") - disconnected: " + e,
+            (Some(address), false, None) => self.name.clone() + " (" + address + ") - connecting",
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SensorKind {
+    #[default]
+    Fan,
+    Voltage,
+    Current,
+    Power,
+}
+
+impl SensorKind {
+
+    /// The sysfs file prefix and the factor from the sysfs unit (RPM, mV,
+    /// mA, µW) to the displayed one (RPM, V, A, W).
+    fn sysfs(&self) -> (&'static str, f64) {
+        match self {
+            SensorKind::Fan => ("fan", 1.0),
+            SensorKind::Voltage => ("in", 1_000.0),
+            SensorKind::Current => ("curr", 1_000.0),
+            SensorKind::Power => ("power", 1_000_000.0),
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Fan => "RPM",
+            SensorKind::Voltage => "V",
+            SensorKind::Current => "A",
+            SensorKind::Power => "W",
+        }
+    }
+}
+
+/// One `<kind><n>_input` reading of a chip with its optional limits.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sensor {
+    pub kind: SensorKind,
+    pub label: String,
+    pub value: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub alarm: bool,
+}
+
+impl fmt::Display for Sensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or("-".to_owned());
+        write!(f, "{}: {:.2} {} (min {}, max {}){}", self.label, self.value, self.kind.unit(),
+               limit(self.min), limit(self.max), if self.alarm { " ALARM" } else { "" })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Chip {
+    pub name: String,
+    pub path: String,
+    pub sensors: Vec<Sensor>,
+}
+
+impl Chip {
+
+    pub fn read(dir: &Path) -> Chip {
+        let mut sensors: Vec<Sensor> = Vec::new();
+        let files: Vec<String> = fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+
+        for kind in [SensorKind::Fan, SensorKind::Voltage, SensorKind::Current, SensorKind::Power] {
+            let (prefix, scale) = kind.sysfs();
+            let mut indexes: Vec<u32> = files.iter()
+                .filter_map(|f| f.strip_prefix(prefix)?.strip_suffix("_input")?.parse::<u32>().ok())
+                .collect();
+            indexes.sort_unstable();
+            for i in indexes {
+                let base = format!("{}{}", prefix, i);
+                let read = |attr: &str| read_number(&dir.join(format!("{}_{}", base, attr)));
+                let Some(value) = read("input") else { continue };
+                sensors.push(Sensor {
+                    kind,
+                    label: fs::read_to_string(dir.join(format!("{}_label", base)))
+                        .map(|l| l.trim().to_owned())
+                        .unwrap_or(base.clone()),
+                    value: value / scale,
+                    min: read("min").map(|v| v / scale),
+                    max: read("max").map(|v| v / scale),
+                    alarm: read("alarm").is_some_and(|a| a != 0.0),
+                });
+            }
+        }
+
+        Chip {
+            name: fs::read_to_string(dir.join("name")).map(|n| n.trim().to_owned()).unwrap_or("unknown".to_owned()),
+            path: dir.to_string_lossy().into_owned(),
+            sensors,
+        }
+    }
+
+    pub fn has_alarm(&self) -> bool {
+        self.sensors.iter().any(|s| s.alarm)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HwmonData {
+    pub chips: Vec<Chip>,
+}
+
+impl HwmonData {
+
+    pub fn new() -> HwmonData {
+        HwmonData::from_root(Path::new(HWMON_ROOT))
+    }
+
+    /// Reads every `hwmon*` chip under `root` that has at least one fan,
+    /// voltage, current or power sensor.
+    pub fn from_root(root: &Path) -> HwmonData {
+        let mut chips: Vec<Chip> = fs::read_dir(root)
+            .map(|entries| entries.flatten().map(|e| Chip::read(&e.path())).collect())
+            .unwrap_or_default();
+        chips.retain(|c| !c.sensors.is_empty());
+        chips.sort_by(|a, b| a.path.cmp(&b.path));
+        HwmonData { chips }
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Hwmon data***");
+        for chip in &self.chips {
+            println!("chip............{} ({})", chip.name, chip.path);
+            for sensor in &chip.sensors {
+                println!("    {}", sensor);
+            }
+        }
+    }
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok().and_then(|s| s.trim().parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    fn sensor(kind: SensorKind, label: &str, value: f64, min: Option<f64>, max: Option<f64>, alarm: bool) -> Sensor {
+        Sensor { kind, label: label.to_owned(), value, min, max, alarm }
+    }
+
+    #[test]
+    fn reads_a_fixture_tree() {
+        let fixture = Fixture::new("hwmon");
+        fixture.write("hwmon2/name", "nct6798\n");
+        fixture.write("hwmon2/fan1_input", "1180\n");
+        fixture.write("hwmon2/fan1_label", "CPU fan\n");
+        fixture.write("hwmon2/fan1_min", "300\n");
+        fixture.write("hwmon2/fan1_alarm", "0\n");
+        fixture.write("hwmon2/fan2_input", "0\n");
+        fixture.write("hwmon2/fan2_alarm", "1\n");
+        fixture.write("hwmon2/in0_input", "1216\n");
+        fixture.write("hwmon2/in0_label", "Vcore\n");
+        fixture.write("hwmon2/in0_min", "800\n");
+        fixture.write("hwmon2/in0_max", "1744\n");
+        // no input: not a reading
+        fixture.write("hwmon2/in1_label", "AVCC\n");
+        fixture.write("hwmon0/name", "BAT0\n");
+        fixture.write("hwmon0/curr1_input", "1530\n");
+        fixture.write("hwmon0/power1_input", "12500000\n");
+        fixture.write("hwmon0/power1_max", "65000000\n");
+        // temperatures are left to the components view
+        fixture.write("hwmon1/name", "coretemp\n");
+        fixture.write("hwmon1/temp1_input", "45000\n");
+
+        let data = HwmonData::from_root(fixture.path());
+        let chips: Vec<(&str, &[Sensor])> = data.chips.iter().map(|c| (c.name.as_str(), c.sensors.as_slice())).collect();
+        assert_eq!(chips, [
+            ("BAT0", &[
+                sensor(SensorKind::Current, "curr1", 1.53, None, None, false),
+                sensor(SensorKind::Power, "power1", 12.5, None, Some(65.0), false),
+            ][..]),
+            ("nct6798", &[
+                sensor(SensorKind::Fan, "CPU fan", 1180.0, Some(300.0), None, false),
+                sensor(SensorKind::Fan, "fan2", 0.0, None, None, true),
+                sensor(SensorKind::Voltage, "Vcore", 1.216, Some(0.8), Some(1.744), false),
+            ][..]),
+        ]);
+        assert!(data.chips[1].has_alarm());
+        assert!(!data.chips[0].has_alarm());
+        assert_eq!(data.chips[1].sensors[2].to_string(), "Vcore: 1.22 V (min 0.80, max 1.74)");
+    }
+}
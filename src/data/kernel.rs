@@ -0,0 +1,173 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+/// One row of `/proc/interrupts` or `/proc/softirqs`: the source and how
+/// many times it fired on every CPU since boot.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IrqCounter {
+    pub name: String,
+    pub description: String,
+    pub per_cpu: Vec<u64>,
+}
+
+/// Kernel activity counters since boot: the `ctxt`, `intr`, `processes`,
+/// `procs_running` and `procs_blocked` lines of `/proc/stat` plus every
+/// interrupt and softirq source.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KernelData {
+    pub context_switches: u64,
+    pub interrupts: u64,
+    pub forks: u64,
+    pub procs_running: u64,
+    pub procs_blocked: u64,
+    pub irqs: Vec<IrqCounter>,
+    pub softirqs: Vec<IrqCounter>,
+}
+
+impl KernelData {
+
+    pub fn new() -> KernelData {
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+        let mut data = KernelData {
+            irqs: parse_counters(&read("/proc/interrupts")),
+            softirqs: parse_counters(&read("/proc/softirqs")),
+            ..KernelData::default()
+        };
+        data.parse_stat(&read("/proc/stat"));
+        data
+    }
+
+    fn parse_stat(&mut self, text: &str) {
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let key = fields.next();
+            // `intr` is followed by one count per IRQ number; the first is the total
+            let value = fields.next().and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+            match key {
+                Some("ctxt") => self.context_switches = value,
+                Some("intr") => self.interrupts = value,
+                Some("processes") => self.forks = value,
+                Some("procs_running") => self.procs_running = value,
+                Some("procs_blocked") => self.procs_blocked = value,
+                _ => {}
+            }
+        }
+    }
+
+    /// The number of CPUs the interrupt counters are split across.
+    pub fn cpu_count(&self) -> usize {
+        self.irqs.iter().chain(&self.softirqs).map(|c| c.per_cpu.len()).max().unwrap_or(0)
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Kernel data***");
+        println!("context switches............{}", self.context_switches);
+        println!("interrupts............{}", self.interrupts);
+        println!("forks............{}", self.forks);
+        println!("running / blocked............{} / {}", self.procs_running, self.procs_blocked);
+        for irq in self.irqs.iter().chain(&self.softirqs) {
+            println!("{}............{:?} {}", irq.name, irq.per_cpu, irq.description);
+        }
+    }
+}
+
+/// Parses `/proc/interrupts` or `/proc/softirqs`: a `CPU0 CPU1 ...` header,
+/// then `name: count count ... description` rows. Some rows (`ERR`, `MIS`)
+/// have a single system-wide count, which is kept as is.
+fn parse_counters(text: &str) -> Vec<IrqCounter> {
+    let mut lines = text.lines();
+    let cpus = lines.next().map(|header| header.split_whitespace().count()).unwrap_or(0);
+    lines.filter_map(|line| {
+        let (name, rest) = line.split_once(':')?;
+        let mut per_cpu: Vec<u64> = Vec::new();
+        let mut fields = rest.split_whitespace().peekable();
+        while per_cpu.len() < cpus {
+            match fields.peek().and_then(|f| f.parse::<u64>().ok()) {
+                Some(count) => {
+                    per_cpu.push(count);
+                    fields.next();
+                }
+                None => break,
+            }
+        }
+        Some(IrqCounter {
+            name: name.trim().to_owned(),
+            description: fields.collect::<Vec<_>>().join(" "),
+            per_cpu,
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERRUPTS: &str = "           CPU0       CPU1       CPU2       CPU3
+   0:         35          0          0          0   IO-APIC   2-edge      timer
+   8:          0          0          1          0   IO-APIC   8-edge      rtc0
+ 127:     120034          0      88020          0   PCI-MSIX-0000:00:14.3    0-edge      iwlwifi:default_queue
+ NMI:         12         11         10          9   Non-maskable interrupts
+ LOC:    9983450    8734512    9012345    8801234   Local timer interrupts
+ ERR:          0
+ MIS:          0
+";
+
+    fn counter(name: &str, description: &str, per_cpu: &[u64]) -> IrqCounter {
+        IrqCounter { name: name.to_owned(), description: description.to_owned(), per_cpu: per_cpu.to_vec() }
+    }
+
+    #[test]
+    fn parses_interrupts() {
+        let irqs = parse_counters(INTERRUPTS);
+        assert_eq!(irqs, [
+            counter("0", "IO-APIC 2-edge timer", &[35, 0, 0, 0]),
+            counter("8", "IO-APIC 8-edge rtc0", &[0, 0, 1, 0]),
+            counter("127", "PCI-MSIX-0000:00:14.3 0-edge iwlwifi:default_queue", &[120034, 0, 88020, 0]),
+            counter("NMI", "Non-maskable interrupts", &[12, 11, 10, 9]),
+            counter("LOC", "Local timer interrupts", &[9983450, 8734512, 9012345, 8801234]),
+            counter("ERR", "", &[0]),
+            counter("MIS", "", &[0]),
+        ]);
+        let data = KernelData { irqs, ..KernelData::default() };
+        assert_eq!(data.cpu_count(), 4);
+    }
+
+    #[test]
+    fn parses_softirqs() {
+        let text = "                    CPU0       CPU1
+          HI:          1          0
+       TIMER:     361823     298711
+      NET_RX:       4521       3309
+";
+        assert_eq!(parse_counters(text), [
+            counter("HI", "", &[1, 0]),
+            counter("TIMER", "", &[361823, 298711]),
+            counter("NET_RX", "", &[4521, 3309]),
+        ]);
+        assert_eq!(parse_counters(""), []);
+    }
+
+    #[test]
+    fn parses_proc_stat_counters() {
+        let text = "cpu  10132153 290696 3084719 46828483 16683 0 25195 0 0 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 0 0
+intr 199292426 35 9 0 0 0 0 3 0 1 0 0 0 4 0 0 0
+ctxt 411212349
+btime 1697612345
+processes 123456
+procs_running 3
+procs_blocked 1
+softirq 98765432 1 36182 3 4521 0 0 2 0 0 0
+";
+        let mut data = KernelData::default();
+        data.parse_stat(text);
+        assert_eq!(
+            (data.context_switches, data.interrupts, data.forks, data.procs_running, data.procs_blocked),
+            (411212349, 199292426, 123456, 3, 1),
+        );
+    }
+}
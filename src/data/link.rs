@@ -0,0 +1,228 @@
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::net::IpAddr;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const SYS_CLASS_NET: &str = "/sys/class/net";
+
+/// ARPHRD_LOOPBACK in `/sys/class/net/<name>/type`.
+const ARPHRD_LOOPBACK: u32 = 772;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterfaceKind {
+    #[default]
+    Physical,
+    Wireless,
+    Loopback,
+    Bridge,
+    Bond,
+    Vlan,
+    Veth,
+    Tun,
+    Virtual,
+}
+
+impl fmt::Display for InterfaceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InterfaceKind::Physical => "physical",
+            InterfaceKind::Wireless => "wireless",
+            InterfaceKind::Loopback => "loopback",
+            InterfaceKind::Bridge => "bridge",
+            InterfaceKind::Bond => "bond",
+            InterfaceKind::Vlan => "vlan",
+            InterfaceKind::Veth => "veth",
+            InterfaceKind::Tun => "tun/tap",
+            InterfaceKind::Virtual => "virtual",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An address of an interface with its prefix length, e.g. `10.0.0.2/24`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceAddress {
+    pub address: IpAddr,
+    pub prefix: u8,
+}
+
+impl fmt::Display for InterfaceAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// The link state and attributes `/sys/class/net/<name>` exposes. Speed,
+/// duplex and carrier are only readable while the link is up.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkInfo {
+    pub kind: InterfaceKind,
+    pub operstate: String,
+    pub carrier: Option<bool>,
+    /// Mbit/s.
+    pub speed: Option<u32>,
+    pub duplex: Option<String>,
+    pub driver: Option<String>,
+    pub master: Option<String>,
+    /// The ports of a bridge or bond.
+    pub slaves: Vec<String>,
+    /// The devices a vlan, macvlan or ipvlan sits on.
+    pub lower: Vec<String>,
+}
+
+impl LinkInfo {
+
+    pub fn read(name: &str) -> LinkInfo {
+        LinkInfo::read_from(&Path::new(SYS_CLASS_NET).join(name))
+    }
+
+    pub fn read_from(dir: &Path) -> LinkInfo {
+        let read = |file: &str| fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_owned());
+        let link_name = |file: &str| fs::read_link(dir.join(file)).ok()
+            .and_then(|target| target.file_name().map(|n| n.to_string_lossy().into_owned()));
+        let mut lower: Vec<String> = fs::read_dir(dir)
+            .map(|entries| entries.flatten()
+                .filter_map(|e| e.file_name().to_str()?.strip_prefix("lower_").map(|n| n.to_owned()))
+                .collect())
+            .unwrap_or_default();
+        lower.sort();
+        let kind = interface_kind(dir, &read, !lower.is_empty());
+        // the lower devices of a bridge or bond are its ports
+        let slaves = if matches!(kind, InterfaceKind::Bridge | InterfaceKind::Bond) {
+            mem::take(&mut lower)
+        } else {
+            Vec::new()
+        };
+
+        LinkInfo {
+            kind,
+            operstate: read("operstate").unwrap_or("unknown".to_owned()),
+            carrier: read("carrier").and_then(|c| c.parse::<u8>().ok()).map(|c| c == 1),
+            speed: read("speed").and_then(|s| s.parse::<i64>().ok()).filter(|s| *s > 0).map(|s| s as u32),
+            duplex: read("duplex").filter(|d| d != "unknown"),
+            driver: link_name("device/driver"),
+            master: link_name("master"),
+            slaves,
+            lower,
+        }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.operstate == "up" || (self.operstate == "unknown" && self.carrier == Some(true))
+    }
+}
+
+fn interface_kind(dir: &Path, read: &impl Fn(&str) -> Option<String>, has_lower: bool) -> InterfaceKind {
+    let devtype = read("uevent").unwrap_or_default()
+        .lines()
+        .find_map(|line| line.strip_prefix("DEVTYPE=").map(|t| t.to_owned()));
+    if read("type").and_then(|t| t.parse::<u32>().ok()) == Some(ARPHRD_LOOPBACK) {
+        InterfaceKind::Loopback
+    } else if dir.join("wireless").exists() || dir.join("phy80211").exists() || devtype.as_deref() == Some("wlan") {
+        InterfaceKind::Wireless
+    } else if dir.join("bridge").exists() {
+        InterfaceKind::Bridge
+    } else if dir.join("bonding").exists() {
+        InterfaceKind::Bond
+    } else if devtype.as_deref() == Some("vlan") {
+        InterfaceKind::Vlan
+    } else if dir.join("tun_flags").exists() {
+        InterfaceKind::Tun
+    } else if dir.join("device").exists() {
+        InterfaceKind::Physical
+    } else if devtype.is_none() && !has_lower && read("iflink") != read("ifindex") {
+        // A veth's iflink is the index of its peer. Tunnels like vxlan
+        // have a DEVTYPE and a macvlan or ipvlan a lower device, while a
+        // veth has neither.
+        InterfaceKind::Veth
+    } else {
+        InterfaceKind::Virtual
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    fn kind(fixture: &Fixture, name: &str) -> InterfaceKind {
+        LinkInfo::read_from(&fixture.path().join(name)).kind
+    }
+
+    #[test]
+    fn tells_interface_kinds_apart() {
+        let fixture = Fixture::new("link-kinds");
+        fixture.write("lo/type", "772\n");
+        fixture.write("wlan0/type", "1\n");
+        fixture.write("wlan0/uevent", "DEVTYPE=wlan\nINTERFACE=wlan0\nIFINDEX=3\n");
+        fixture.write("br0/bridge/stp_state", "0\n");
+        fixture.write("bond0/bonding/mode", "active-backup 1\n");
+        fixture.write("eth0.10/uevent", "DEVTYPE=vlan\nINTERFACE=eth0.10\nIFINDEX=5\n");
+        fixture.write("tap0/tun_flags", "0x1002\n");
+        fixture.write("eth0/device/vendor", "0x8086\n");
+        for (name, index, link) in [("veth1", "7", "6"), ("macvlan0", "8", "2"), ("vxlan0", "9", "2"), ("dummy0", "10", "10")] {
+            fixture.write(&format!("{}/ifindex", name), &format!("{}\n", index));
+            fixture.write(&format!("{}/iflink", name), &format!("{}\n", link));
+        }
+        fixture.link("macvlan0/lower_eth0", "../eth0");
+        fixture.write("vxlan0/uevent", "DEVTYPE=vxlan\nINTERFACE=vxlan0\nIFINDEX=9\n");
+
+        assert_eq!(kind(&fixture, "lo"), InterfaceKind::Loopback);
+        assert_eq!(kind(&fixture, "wlan0"), InterfaceKind::Wireless);
+        assert_eq!(kind(&fixture, "br0"), InterfaceKind::Bridge);
+        assert_eq!(kind(&fixture, "bond0"), InterfaceKind::Bond);
+        assert_eq!(kind(&fixture, "eth0.10"), InterfaceKind::Vlan);
+        assert_eq!(kind(&fixture, "tap0"), InterfaceKind::Tun);
+        assert_eq!(kind(&fixture, "eth0"), InterfaceKind::Physical);
+        assert_eq!(kind(&fixture, "veth1"), InterfaceKind::Veth);
+        assert_eq!(kind(&fixture, "macvlan0"), InterfaceKind::Virtual);
+        assert_eq!(kind(&fixture, "vxlan0"), InterfaceKind::Virtual);
+        assert_eq!(kind(&fixture, "dummy0"), InterfaceKind::Virtual);
+    }
+
+    #[test]
+    fn reads_link_attributes() {
+        let fixture = Fixture::new("link-attributes");
+        fixture.write("eth0/operstate", "up\n");
+        fixture.write("eth0/carrier", "1\n");
+        fixture.write("eth0/speed", "1000\n");
+        fixture.write("eth0/duplex", "full\n");
+        fixture.link("eth0/device/driver", "../../drivers/e1000e");
+        fixture.link("eth0/master", "../bond0");
+        fixture.write("bond0/bonding/mode", "active-backup 1\n");
+        fixture.write("bond0/operstate", "up\n");
+        fixture.link("bond0/lower_eth1", "../eth1");
+        fixture.link("bond0/lower_eth0", "../eth0");
+        fixture.write("eth0.10/uevent", "DEVTYPE=vlan\n");
+        fixture.write("eth0.10/operstate", "down\n");
+        // unreadable while the link is down
+        fixture.write("eth0.10/speed", "-1\n");
+        fixture.write("eth0.10/duplex", "unknown\n");
+        fixture.link("eth0.10/lower_eth0", "../eth0");
+
+        let eth0 = LinkInfo::read_from(&fixture.path().join("eth0"));
+        assert_eq!(eth0, LinkInfo {
+            kind: InterfaceKind::Physical,
+            operstate: "up".to_owned(),
+            carrier: Some(true),
+            speed: Some(1000),
+            duplex: Some("full".to_owned()),
+            driver: Some("e1000e".to_owned()),
+            master: Some("bond0".to_owned()),
+            slaves: Vec::new(),
+            lower: Vec::new(),
+        });
+        assert!(eth0.is_up());
+
+        let bond = LinkInfo::read_from(&fixture.path().join("bond0"));
+        assert_eq!((bond.slaves, bond.lower), (vec!["eth0".to_owned(), "eth1".to_owned()], Vec::new()));
+
+        let vlan = LinkInfo::read_from(&fixture.path().join("eth0.10"));
+        assert_eq!((vlan.speed, vlan.duplex.as_deref(), vlan.is_up()), (None, None, false));
+        assert_eq!((vlan.slaves, vlan.lower), (Vec::new(), vec!["eth0".to_owned()]));
+    }
+}
@@ -1,2 +1,24 @@
+pub mod blockdev;
+pub mod cgroup;
+pub mod chart;
+pub mod filesystem;
+#[cfg(test)]
+mod fixture;
+pub mod host;
+pub mod hwmon;
+pub mod kernel;
+pub mod link;
+pub mod options;
+pub mod pressure;
+pub mod process;
+pub mod query;
+pub mod record;
+pub mod remote;
+pub mod routes;
+pub mod sessions;
+pub mod sockets;
 pub mod structs;
-pub mod vew_data;
\ No newline at end of file
+pub mod summary;
+pub mod vew_data;
+pub mod watchdog;
+pub mod wireless;
\ No newline at end of file
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::remote;
+
+pub const USAGE: &str = "usage: monitoring_system [--record <file>] [--replay <file>] [--connect <host[:port]>]... [--interval <seconds>] [--mini]
+       monitoring_system agent [--listen <address>] [--interval <seconds>]
+       monitoring_system ps [<query>...]      e.g. ps state:D, ps 'user:ci cpu>20 mem>1GiB name~java'";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Gui,
+    Agent,
+    Ps,
+}
+
+/// Command line options of the application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub connect: Vec<String>,
+    /// Where the agent listens; loopback only unless told otherwise, as
+    /// it has no authentication.
+    pub listen: String,
+    pub interval: Duration,
+    /// The process query of `ps`.
+    pub query: String,
+    /// Start with the mini monitor instead of the main window.
+    pub mini: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            mode: Mode::Gui,
+            record: None,
+            replay: None,
+            connect: Vec::new(),
+            listen: format!("127.0.0.1:{}", remote::DEFAULT_PORT),
+            interval: Duration::from_secs(2),
+            query: String::new(),
+            mini: false,
+        }
+    }
+}
+
+impl Options {
+
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut flags: Vec<String> = Vec::new();
+        while let Some(arg) = args.next() {
+            if arg.starts_with("--") {
+                flags.push(arg.clone());
+            }
+            match arg.as_str() {
+                "agent" if options.mode == Mode::Gui => options.mode = Mode::Agent,
+                // everything after `ps` is the query, which may start with `-` or `!`
+                "ps" if options.mode == Mode::Gui => {
+                    options.mode = Mode::Ps;
+                    options.query = args.by_ref().collect::<Vec<_>>().join(" ");
+                }
+                "--record" => options.record = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--replay" => options.replay = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--connect" => options.connect.push(remote::with_default_port(&value(&arg, args.next())?)),
+                "--listen" => options.listen = value(&arg, args.next())?,
+                "--mini" => options.mini = true,
+                "--interval" => {
+                    let secs = value(&arg, args.next())?;
+                    match secs.parse::<f64>() {
+                        Ok(secs) if secs > 0.0 => options.interval = Duration::from_secs_f64(secs),
+                        _ => return Err(format!("invalid interval: {}\n{}", secs, USAGE)),
+                    }
+                }
+                "-h" | "--help" => return Err(USAGE.to_owned()),
+                _ => return Err(format!("unknown argument: {}\n{}", arg, USAGE)),
+            }
+        }
+        if options.record.is_some() && options.replay.is_some() {
+            return Err("--record and --replay can't be used together".to_owned());
+        }
+        let (allowed, mode): (&[&str], &str) = match options.mode {
+            Mode::Gui => (&["--record", "--replay", "--connect", "--interval", "--mini"], "without agent"),
+            Mode::Agent => (&["--listen", "--interval"], "in agent mode"),
+            Mode::Ps => (&[], "with ps"),
+        };
+        if let Some(flag) = flags.iter().find(|flag| !allowed.contains(&flag.as_str())) {
+            return Err(format!("{} can't be used {}\n{}", flag, mode, USAGE));
+        }
+        Ok(options)
+    }
+}
+
+fn value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or(format!("{} requires a value\n{}", flag, USAGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        Options::from_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn agent_rejects_gui_options() {
+        let agent = parse("agent --listen 127.0.0.1:9000 --interval 5").unwrap();
+        assert_eq!((agent.mode, agent.listen.as_str(), agent.interval), (Mode::Agent, "127.0.0.1:9000", Duration::from_secs(5)));
+        for flag in ["--record out.jsonl", "--replay in.jsonl", "--connect db1", "--mini"] {
+            let error = parse(&format!("agent {}", flag)).unwrap_err();
+            assert!(error.starts_with(&format!("{} can't be used in agent mode", flag.split(' ').next().unwrap())), "{}", error);
+        }
+        assert!(parse("--mini --connect db1").is_ok());
+        assert_eq!(parse("agent").unwrap().listen, "127.0.0.1:7878");
+    }
+
+    #[test]
+    fn rejects_options_the_mode_ignores() {
+        assert!(parse("--listen 0.0.0.0:9000").unwrap_err().starts_with("--listen can't be used without agent"));
+        for flags in ["--mini", "--interval 1", "--connect db1"] {
+            let error = parse(&format!("{} ps state:D", flags)).unwrap_err();
+            assert!(error.starts_with(&format!("{} can't be used with ps", flags.split(' ').next().unwrap())), "{}", error);
+        }
+        // everything after ps is the query
+        assert_eq!(parse("ps --mini").unwrap().query, "--mini");
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const PRESSURE_ROOT: &str = "/proc/pressure";
+
+/// One line of a PSI file: the share of time (in %) tasks were stalled over
+/// the last 10, 60 and 300 seconds and the total stall time in microseconds.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PsiLine {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total: u64,
+}
+
+/// The content of a `/proc/pressure/*` or `<cgroup>/*.pressure` file. `full`
+/// is missing for CPU pressure on kernels older than 5.13.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pressure {
+    pub some: PsiLine,
+    pub full: Option<PsiLine>,
+}
+
+impl Pressure {
+
+    pub fn read(path: &Path) -> Option<Pressure> {
+        fs::read_to_string(path).ok().and_then(|text| Pressure::parse(&text))
+    }
+
+    /// Parses lines like `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`.
+    pub fn parse(text: &str) -> Option<Pressure> {
+        let mut some: Option<PsiLine> = None;
+        let mut full: Option<PsiLine> = None;
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next();
+            let mut psi = PsiLine::default();
+            for field in fields {
+                let Some((key, value)) = field.split_once('=') else { continue };
+                match key {
+                    "avg10" => psi.avg10 = value.parse().unwrap_or(0.0),
+                    "avg60" => psi.avg60 = value.parse().unwrap_or(0.0),
+                    "avg300" => psi.avg300 = value.parse().unwrap_or(0.0),
+                    "total" => psi.total = value.parse().unwrap_or(0),
+                    _ => {}
+                }
+            }
+            match kind {
+                Some("some") => some = Some(psi),
+                Some("full") => full = Some(psi),
+                _ => {}
+            }
+        }
+        some.map(|some| Pressure { some, full })
+    }
+}
+
+/// CPU, memory and I/O pressure of the whole system or of one cgroup.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PressureData {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+impl PressureData {
+
+    /// Reads the system-wide pressure from `/proc/pressure`.
+    pub fn new() -> PressureData {
+        PressureData::read(Path::new(PRESSURE_ROOT), "")
+    }
+
+    /// Reads `cpu<suffix>`, `memory<suffix>` and `io<suffix>` from `dir`.
+    pub fn read(dir: &Path, suffix: &str) -> PressureData {
+        PressureData {
+            cpu: Pressure::read(&dir.join(format!("cpu{}", suffix))),
+            memory: Pressure::read(&dir.join(format!("memory{}", suffix))),
+            io: Pressure::read(&dir.join(format!("io{}", suffix))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    #[test]
+    fn parses_a_cpu_file_without_full() {
+        let pressure = Pressure::parse("some avg10=1.53 avg60=0.87 avg300=0.25 total=76599035\n").unwrap();
+        assert_eq!(pressure, Pressure {
+            some: PsiLine { avg10: 1.53, avg60: 0.87, avg300: 0.25, total: 76599035 },
+            full: None,
+        });
+    }
+
+    #[test]
+    fn parses_some_and_full() {
+        let text = "some avg10=12.00 avg60=8.50 avg300=2.10 total=4000000\nfull avg10=4.00 avg60=2.00 avg300=0.50 total=1000000\n";
+        let pressure = Pressure::parse(text).unwrap();
+        assert_eq!(pressure.some.avg10, 12.0);
+        assert_eq!(pressure.full, Some(PsiLine { avg10: 4.0, avg60: 2.0, avg300: 0.5, total: 1000000 }));
+    }
+
+    #[test]
+    fn skips_malformed_fields() {
+        let pressure = Pressure::parse("some avg10=abc avg60 =2.00 avg300=0.50 total=-1 extra=1\nfull avg10=1.00\n").unwrap();
+        assert_eq!(pressure.some, PsiLine { avg300: 0.5, ..PsiLine::default() });
+        assert_eq!(pressure.full, Some(PsiLine { avg10: 1.0, ..PsiLine::default() }));
+        // no `some` line, nothing to show
+        assert_eq!(Pressure::parse("full avg10=1.00 avg60=0.00 avg300=0.00 total=5\n"), None);
+        assert_eq!(Pressure::parse(""), None);
+    }
+
+    #[test]
+    fn reads_files_with_a_suffix() {
+        let fixture = Fixture::new("pressure");
+        fixture.write("cpu.pressure", "some avg10=1.00 avg60=0.00 avg300=0.00 total=10\n");
+        fixture.write("io.pressure", "some avg10=3.00 avg60=0.00 avg300=0.00 total=30\nfull avg10=2.00 avg60=0.00 avg300=0.00 total=20\n");
+        // the name /proc/pressure uses, not read with a suffix
+        fixture.write("memory", "some avg10=9.00 avg60=0.00 avg300=0.00 total=90\n");
+
+        let cgroup = PressureData::read(fixture.path(), ".pressure");
+        assert_eq!(cgroup.cpu.map(|p| p.some.total), Some(10));
+        assert_eq!(cgroup.memory, None);
+        assert_eq!(cgroup.io.and_then(|p| p.full).map(|f| f.total), Some(20));
+
+        let system = PressureData::read(fixture.path(), "");
+        assert_eq!((system.cpu, system.memory.map(|p| p.some.total), system.io), (None, Some(90), None));
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// One process of `/proc`. CPU time is in clock ticks and only means
+/// something as a difference between two samples.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub uid: u32,
+    pub user: String,
+    /// The one-letter state of `/proc/<pid>/stat`: R, S, D, Z, T, I...
+    pub state: String,
+    pub threads: u64,
+    pub rss: u64,
+    pub vsize: u64,
+    pub cpu_ticks: u64,
+    pub start_ticks: u64,
+    pub priority: i64,
+    pub nice: i64,
+}
+
+impl ProcessInfo {
+
+    pub fn read(pid: u32, users: &HashMap<u32, String>) -> Option<ProcessInfo> {
+        let dir = Path::new("/proc").join(pid.to_string());
+        let stat = fs::read_to_string(dir.join("stat")).ok()?;
+        let uid = owner(&dir);
+        let cmdline = fs::read(dir.join("cmdline")).unwrap_or_default()
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut info = ProcessInfo::parse_stat(&stat)?;
+        info.cmdline = cmdline;
+        info.uid = uid;
+        info.user = users.get(&uid).cloned().unwrap_or(uid.to_string());
+        Some(info)
+    }
+
+    /// Parses `/proc/<pid>/stat`. The name is in parentheses and may itself
+    /// contain spaces and parentheses, so fields are counted from the last `)`.
+    pub fn parse_stat(stat: &str) -> Option<ProcessInfo> {
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+        // fields[0] is field 3 of proc(5)
+        let field = |n: usize| fields.get(n - 3).copied().unwrap_or("0");
+        let number = |n: usize| field(n).parse::<u64>().unwrap_or(0);
+        let signed = |n: usize| field(n).parse::<i64>().unwrap_or(0);
+        Some(ProcessInfo {
+            pid: stat[..open].trim().parse().ok()?,
+            ppid: number(4) as u32,
+            name: stat[open + 1..close].to_owned(),
+            state: field(3).to_owned(),
+            cpu_ticks: number(14) + number(15),
+            priority: signed(18),
+            nice: signed(19),
+            threads: number(20),
+            start_ticks: number(22),
+            vsize: number(23),
+            rss: number(24) * page_size(),
+            ..ProcessInfo::default()
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessesData {
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl ProcessesData {
+
+    pub fn new() -> ProcessesData {
+        let users = read_users();
+        let mut processes: Vec<ProcessInfo> = fs::read_dir("/proc")
+            .map(|entries| entries.flatten()
+                .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+                .filter_map(|pid| ProcessInfo::read(pid, &users))
+                .collect())
+            .unwrap_or_default();
+        processes.sort_by_key(|p| p.pid);
+        ProcessesData { processes }
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&ProcessInfo> {
+        self.processes.binary_search_by_key(&pid, |p| p.pid).ok().map(|i| &self.processes[i])
+    }
+
+    /// CPU usage of every process since `prev` in % of one CPU, so a
+    /// process keeping two cores busy is at 200%. `elapsed_ticks` is the
+    /// time that passed on one CPU, see `CpuData::elapsed_ticks`.
+    pub fn cpu_percent(&self, prev: &ProcessesData, elapsed_ticks: f64) -> HashMap<u32, f32> {
+        if elapsed_ticks <= 0.0 {
+            return HashMap::new();
+        }
+        self.processes.iter()
+            .filter_map(|p| {
+                let before = prev.get(p.pid).filter(|b| b.start_ticks == p.start_ticks)?;
+                Some((p.pid, (p.cpu_ticks.saturating_sub(before.cpu_ticks) as f64 * 100.0 / elapsed_ticks) as f32))
+            })
+            .collect()
+    }
+
+    /// The children of every pid; processes whose parent is gone (pid 1,
+    /// kthreadd) are under pid 0.
+    pub fn children(&self) -> HashMap<u32, Vec<&ProcessInfo>> {
+        let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+        for process in &self.processes {
+            let parent = if self.get(process.ppid).is_some() { process.ppid } else { 0 };
+            children.entry(parent).or_default().push(process);
+        }
+        children
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Processes data***");
+        for p in &self.processes {
+            println!("{:>7} {:>7} {:<8} {} {:<16} {}", p.pid, p.ppid, p.user, p.state, p.name, p.cmdline);
+        }
+    }
+}
+
+/// User names by uid from `/etc/passwd`.
+pub fn read_users() -> HashMap<u32, String> {
+    fs::read_to_string("/etc/passwd").unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.get(2)?.parse().ok()?, fields[0].to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn owner(path: &Path) -> u32 {
+    fs::metadata(path).map(|m| m.uid()).unwrap_or(0)
+}
+
+/// There's no `/proc` to read outside Unix anyway.
+#[cfg(not(unix))]
+fn owner(_path: &Path) -> u32 {
+    0
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 { size as u64 } else { 4096 }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+/// Everything `/proc/<pid>` tells about one process, read on demand for the
+/// inspector. Parts that need more privileges than we have are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessDetail {
+    pub info: ProcessInfo,
+    pub cwd: Option<String>,
+    pub exe: Option<String>,
+    pub environment: Option<Vec<String>>,
+    /// Real, effective, saved and filesystem ids of the `Uid:`/`Gid:` lines.
+    pub uids: Vec<u32>,
+    pub gids: Vec<u32>,
+    pub groups: Vec<u32>,
+    pub policy: String,
+    pub rt_priority: u64,
+    /// Thread id, name and state.
+    pub threads: Vec<(u32, String, String)>,
+    /// File descriptor number and what it points to.
+    pub fds: Option<Vec<(u32, String)>>,
+    pub smaps_rollup: Option<Vec<(String, u64)>>,
+    pub io: Option<Vec<(String, u64)>>,
+    /// Name, soft limit, hard limit and unit of every `limits` line.
+    pub limits: Vec<(String, String, String, String)>,
+}
+
+impl ProcessDetail {
+
+    /// `None` once the process is gone.
+    pub fn read(pid: u32) -> Option<ProcessDetail> {
+        let dir = Path::new("/proc").join(pid.to_string());
+        let info = ProcessInfo::read(pid, &read_users())?;
+        let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+        let link = |file: &str| fs::read_link(dir.join(file)).ok().map(|p| p.to_string_lossy().into_owned());
+        let status = read("status").unwrap_or_default();
+        let ids = |key: &str| -> Vec<u32> {
+            status.lines()
+                .find_map(|line| line.strip_prefix(key))
+                .map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()).collect())
+                .unwrap_or_default()
+        };
+        // fields 40 and 41 of proc(5), counted from the last `)` like parse_stat
+        let stat = read("stat").unwrap_or_default();
+        let tail: Vec<&str> = stat.rfind(')').map(|i| stat[i + 1..].split_whitespace().collect()).unwrap_or_default();
+        let stat_field = |n: usize| tail.get(n - 3).and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+
+        let mut threads: Vec<(u32, String, String)> = fs::read_dir(dir.join("task"))
+            .map(|entries| entries.flatten()
+                .filter_map(|e| {
+                    let stat = fs::read_to_string(e.path().join("stat")).ok()?;
+                    let thread = ProcessInfo::parse_stat(&stat)?;
+                    Some((thread.pid, thread.name, thread.state))
+                })
+                .collect())
+            .unwrap_or_default();
+        threads.sort();
+        let fds = fs::read_dir(dir.join("fd")).ok().map(|entries| {
+            let mut fds: Vec<(u32, String)> = entries.flatten()
+                .filter_map(|e| {
+                    let fd = e.file_name().to_str()?.parse().ok()?;
+                    Some((fd, fs::read_link(e.path()).map(|t| t.to_string_lossy().into_owned()).unwrap_or_default()))
+                })
+                .collect();
+            fds.sort();
+            fds
+        });
+
+        Some(ProcessDetail {
+            cwd: link("cwd"),
+            exe: link("exe"),
+            environment: fs::read(dir.join("environ")).ok().map(|env| {
+                env.split(|b| *b == 0)
+                    .filter(|var| !var.is_empty())
+                    .map(|var| String::from_utf8_lossy(var).into_owned())
+                    .collect()
+            }),
+            uids: ids("Uid:"),
+            gids: ids("Gid:"),
+            groups: ids("Groups:"),
+            policy: policy_name(stat_field(41)).to_owned(),
+            rt_priority: stat_field(40),
+            threads,
+            fds,
+            smaps_rollup: read("smaps_rollup").map(|text| parse_kb_lines(&text)),
+            io: read("io").map(|text| {
+                text.lines()
+                    .filter_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        Some((key.to_owned(), value.trim().parse().ok()?))
+                    })
+                    .collect()
+            }),
+            limits: read("limits").map(|text| parse_limits(&text)).unwrap_or_default(),
+            info,
+        })
+    }
+}
+
+/// `sched_setscheduler(2)` policies.
+fn policy_name(policy: u64) -> &'static str {
+    match policy {
+        0 => "SCHED_OTHER",
+        1 => "SCHED_FIFO",
+        2 => "SCHED_RR",
+        3 => "SCHED_BATCH",
+        5 => "SCHED_IDLE",
+        6 => "SCHED_DEADLINE",
+        _ => "unknown",
+    }
+}
+
+/// `Rss:                9696 kB` lines in bytes; the address range header
+/// of smaps_rollup is skipped.
+fn parse_kb_lines(text: &str) -> Vec<(String, u64)> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let kb = value.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+            Some((key.to_owned(), kb * 1024))
+        })
+        .collect()
+}
+
+/// The `limits` table has fixed-width columns: a 26 character name, then
+/// 21 characters each for the soft and hard limit, then the unit.
+fn parse_limits(text: &str) -> Vec<(String, String, String, String)> {
+    text.lines()
+        .skip(1)
+        .map(|line| {
+            let column = |from: usize, to: usize| line.get(from..to.min(line.len())).unwrap_or_default().trim().to_owned();
+            (column(0, 26), column(26, 47), column(47, 68), column(68, line.len()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: u32) -> ProcessInfo {
+        ProcessInfo { pid, ppid, ..ProcessInfo::default() }
+    }
+
+    #[test]
+    fn parses_stat_with_parentheses_in_the_name() {
+        let stat = "4321 (my ) (proc) R 1 4321 4321 0 -1 4194560 1000 0 0 0 150 50 0 0 20 -5 3 0 98765 12345678 2500 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 2 0 0 0 0 0\n";
+        let info = ProcessInfo::parse_stat(stat).unwrap();
+        assert_eq!(info, ProcessInfo {
+            pid: 4321,
+            ppid: 1,
+            name: "my ) (proc".to_owned(),
+            state: "R".to_owned(),
+            cpu_ticks: 200,
+            priority: 20,
+            nice: -5,
+            threads: 3,
+            start_ticks: 98765,
+            vsize: 12345678,
+            rss: 2500 * page_size(),
+            ..ProcessInfo::default()
+        });
+        assert_eq!(ProcessInfo::parse_stat("4321 no name R 1"), None);
+        assert_eq!(ProcessInfo::parse_stat("(kworker) R 1"), None);
+    }
+
+    #[test]
+    fn puts_orphans_under_pid_0() {
+        // 1 and 2 have parent 0; 300's parent exited
+        let data = ProcessesData { processes: vec![process(1, 0), process(2, 0), process(10, 1), process(11, 10), process(12, 2), process(300, 299)] };
+        let children = data.children();
+        let pids = |parent: u32| children.get(&parent).map(|c| c.iter().map(|p| p.pid).collect::<Vec<_>>()).unwrap_or_default();
+        assert_eq!(pids(0), [1, 2, 300]);
+        assert_eq!(pids(1), [10]);
+        assert_eq!(pids(10), [11]);
+        assert_eq!(pids(2), [12]);
+        assert!(pids(299).is_empty());
+    }
+
+    #[test]
+    fn parses_limits() {
+        let text = "Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max stack size            8388608              unlimited            bytes     
+Max processes             24001                24001                processes 
+Max open files            1024                 524288               files     
+Max nice priority         0                    0                    
+Max realtime timeout      unlimited            unlimited            us        
+Max widget rate           10                   20                   widgets per second
+";
+        let row = |name: &str, soft: &str, hard: &str, unit: &str| (name.to_owned(), soft.to_owned(), hard.to_owned(), unit.to_owned());
+        assert_eq!(parse_limits(text), [
+            row("Max cpu time", "unlimited", "unlimited", "seconds"),
+            row("Max stack size", "8388608", "unlimited", "bytes"),
+            row("Max processes", "24001", "24001", "processes"),
+            row("Max open files", "1024", "524288", "files"),
+            row("Max nice priority", "0", "0", ""),
+            row("Max realtime timeout", "unlimited", "unlimited", "us"),
+            row("Max widget rate", "10", "20", "widgets per second"),
+        ]);
+    }
+
+    #[test]
+    fn parses_smaps_rollup() {
+        let text = "55c3e44fe000-7ffd2acc0000 ---p 00000000 00:00 0                          [rollup]
+Rss:                1252 kB
+Pss:                 433 kB
+Shared_Clean:       1112 kB
+Private_Dirty:       100 kB
+Swap:                  0 kB
+THPeligible:    0
+";
+        let kb = |key: &str, kb: u64| (key.to_owned(), kb * 1024);
+        assert_eq!(parse_kb_lines(text), [kb("Rss", 1252), kb("Pss", 433), kb("Shared_Clean", 1112), kb("Private_Dirty", 100), kb("Swap", 0)]);
+    }
+}
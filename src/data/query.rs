@@ -0,0 +1,344 @@
+//! A small filter language for processes, e.g. `user:ci cpu>20 mem>1GiB
+//! name~java state:D`. A query is a list of terms that all have to match:
+//!
+//! - `field:value` or `field=value`: equal (`state:D`, `user:root`, `pid=1`)
+//! - `field~value`: contains, ignoring case (`name~java`, `cmd~--config`)
+//! - `field>n`, `field<n`, `field>=n`, `field<=n`: numeric comparisons
+//! - a bare word matches the pid or is looked up in the name and command line
+//! - a leading `!` negates a term (`!user:root`)
+//!
+//! Fields are `pid`, `ppid`, `user`, `uid`, `name`, `cmd`, `state`, `cpu`
+//! (in % of one CPU), `mem` (resident memory, with an optional `K`, `KB`,
+//! `KiB`, `M`, ... suffix), `threads` and `nice`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use super::process::{ProcessInfo, ProcessesData};
+use super::structs::{CpuData, CpuTimes};
+
+/// How long the CLI waits between its two samples to compute CPU usage.
+const CLI_SAMPLE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Pid,
+    Ppid,
+    User,
+    Uid,
+    Name,
+    Cmd,
+    State,
+    Cpu,
+    Mem,
+    Threads,
+    Nice,
+}
+
+impl Field {
+
+    fn parse(name: &str) -> Option<Field> {
+        Some(match name {
+            "pid" => Field::Pid,
+            "ppid" => Field::Ppid,
+            "user" => Field::User,
+            "uid" => Field::Uid,
+            "name" => Field::Name,
+            "cmd" => Field::Cmd,
+            "state" => Field::State,
+            "cpu" => Field::Cpu,
+            "mem" | "rss" => Field::Mem,
+            "threads" => Field::Threads,
+            "nice" => Field::Nice,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Field::User | Field::Name | Field::Cmd | Field::State)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Contains,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    /// A bare word.
+    Any(String),
+    Text { field: Field, op: Op, value: String },
+    Number { field: Field, op: Op, value: f64 },
+    Not(Box<Term>),
+}
+
+impl Term {
+
+    fn parse(word: &str) -> Result<Term, String> {
+        if let Some(rest) = word.strip_prefix('!') {
+            return Ok(Term::Not(Box::new(Term::parse(rest)?)));
+        }
+        let Some(at) = word.find([':', '=', '~', '<', '>']) else {
+            return Ok(Term::Any(word.to_lowercase()));
+        };
+        let (name, rest) = word.split_at(at);
+        let field = Field::parse(name).ok_or(format!("unknown field: {}", name))?;
+        let (op, value) = [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt), ("~", Op::Contains), (":", Op::Eq), ("=", Op::Eq)]
+            .into_iter()
+            .find_map(|(symbol, op)| rest.strip_prefix(symbol).map(|value| (op, value)))
+            .ok_or(format!("invalid term: {}", word))?;
+        if value.is_empty() {
+            return Err(format!("missing value in {}", word));
+        }
+        if field.is_numeric() {
+            if op == Op::Contains {
+                return Err(format!("{} is a number, use :, <, > in {}", name, word));
+            }
+            let value = if field == Field::Mem { parse_size(value) } else { value.parse().ok() };
+            let value = value.ok_or(format!("invalid number in {}", word))?;
+            Ok(Term::Number { field, op, value })
+        } else {
+            if !matches!(op, Op::Eq | Op::Contains) {
+                return Err(format!("{} is text, use : or ~ in {}", name, word));
+            }
+            Ok(Term::Text { field, op, value: value.to_owned() })
+        }
+    }
+
+    fn matches(&self, process: &ProcessInfo, cpu: f32) -> bool {
+        match self {
+            Term::Any(word) => {
+                process.pid.to_string() == *word
+                    || process.name.to_lowercase().contains(word)
+                    || process.cmdline.to_lowercase().contains(word)
+            }
+            Term::Text { field, op, value } => {
+                let actual = match field {
+                    Field::User => &process.user,
+                    Field::Name => &process.name,
+                    Field::Cmd => &process.cmdline,
+                    _ => &process.state,
+                };
+                match op {
+                    Op::Contains => actual.to_lowercase().contains(&value.to_lowercase()),
+                    _ => actual == value,
+                }
+            }
+            Term::Number { field, op, value } => {
+                let actual = match field {
+                    Field::Pid => process.pid as f64,
+                    Field::Ppid => process.ppid as f64,
+                    Field::Uid => process.uid as f64,
+                    Field::Cpu => cpu as f64,
+                    Field::Mem => process.rss as f64,
+                    Field::Threads => process.threads as f64,
+                    _ => process.nice as f64,
+                };
+                match op {
+                    Op::Gt => actual > *value,
+                    Op::Lt => actual < *value,
+                    Op::Ge => actual >= *value,
+                    Op::Le => actual <= *value,
+                    _ => actual == *value,
+                }
+            }
+            Term::Not(term) => !term.matches(process, cpu),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+impl Query {
+
+    pub fn parse(text: &str) -> Result<Query, String> {
+        Ok(Query {
+            terms: text.split_whitespace().map(Term::parse).collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// `cpu` is the usage since the previous sample, 0 when unknown.
+    pub fn matches(&self, process: &ProcessInfo, cpu: f32) -> bool {
+        self.terms.iter().all(|term| term.matches(process, cpu))
+    }
+}
+
+/// `512`, `64K`, `1.5MB`, `1GiB`: the single-letter and `iB` suffixes are
+/// powers of 1024, `KB`, `MB`... powers of 1000.
+pub fn parse_size(text: &str) -> Option<f64> {
+    let split = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" | "KIB" => 1024.0,
+        "M" | "MIB" => 1024.0 * 1024.0,
+        "G" | "GIB" => 1024.0 * 1024.0 * 1024.0,
+        "T" | "TIB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    number.parse::<f64>().ok().map(|n| n * multiplier)
+}
+
+/// Where saved queries are kept, one per line:
+/// `$XDG_CONFIG_HOME/monitoring_system/queries` or `~/.config/...`.
+pub fn saved_queries_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("monitoring_system").join("queries"))
+}
+
+pub fn load_saved_queries() -> Vec<String> {
+    saved_queries_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|text| text.lines().filter(|l| !l.trim().is_empty()).map(|l| l.to_owned()).collect())
+        .unwrap_or_default()
+}
+
+pub fn store_saved_queries(queries: &[String]) -> std::io::Result<()> {
+    let Some(path) = saved_queries_path() else { return Ok(()) };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, queries.iter().map(|q| q.clone() + "\n").collect::<String>())
+}
+
+/// The `ps` command: prints the processes matching `query`, busiest first.
+pub fn run_cli(query: &str) -> Result<(), String> {
+    let query = Query::parse(query)?;
+    let sample = || {
+        let (total_times, core_times) = CpuTimes::read_stat();
+        (CpuData { total_times, core_times, ..CpuData::default() }, ProcessesData::new())
+    };
+    let (cpu_before, before) = sample();
+    thread::sleep(CLI_SAMPLE_DELAY);
+    let (cpu_now, now) = sample();
+    let cpu: HashMap<u32, f32> = now.cpu_percent(&before, cpu_now.elapsed_ticks(&cpu_before));
+
+    let usage = |p: &ProcessInfo| cpu.get(&p.pid).copied().unwrap_or(0.0);
+    let mut matching: Vec<&ProcessInfo> = now.processes.iter().filter(|p| query.matches(p, usage(p))).collect();
+    matching.sort_by(|a, b| usage(b).total_cmp(&usage(a)).then(a.pid.cmp(&b.pid)));
+    println!("{:>7} {:>7} {:<12} {:<5} {:>6} {:>10} {:>7}  COMMAND", "PID", "PPID", "USER", "STATE", "CPU%", "RSS", "THREADS");
+    for p in matching {
+        let command = if p.cmdline.is_empty() { format!("[{}]", p.name) } else { p.cmdline.clone() };
+        println!("{:>7} {:>7} {:<12} {:<5} {:>6.1} {:>9}K {:>7}  {}", p.pid, p.ppid, p.user, p.state, usage(p), p.rss / 1024, p.threads, command);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(word: &str) -> Term {
+        Term::parse(word).unwrap()
+    }
+
+    fn number(field: Field, op: Op, value: f64) -> Term {
+        Term::Number { field, op, value }
+    }
+
+    fn text(field: Field, op: Op, value: &str) -> Term {
+        Term::Text { field, op, value: value.to_owned() }
+    }
+
+    #[test]
+    fn parses_every_operator() {
+        assert_eq!(term("pid=1"), number(Field::Pid, Op::Eq, 1.0));
+        assert_eq!(term("pid:1"), number(Field::Pid, Op::Eq, 1.0));
+        assert_eq!(term("cpu>20"), number(Field::Cpu, Op::Gt, 20.0));
+        assert_eq!(term("cpu<20"), number(Field::Cpu, Op::Lt, 20.0));
+        assert_eq!(term("cpu>=20"), number(Field::Cpu, Op::Ge, 20.0));
+        assert_eq!(term("cpu<=20"), number(Field::Cpu, Op::Le, 20.0));
+        assert_eq!(term("threads>=4"), number(Field::Threads, Op::Ge, 4.0));
+        assert_eq!(term("nice<0"), number(Field::Nice, Op::Lt, 0.0));
+        assert_eq!(term("user:root"), text(Field::User, Op::Eq, "root"));
+        assert_eq!(term("state=D"), text(Field::State, Op::Eq, "D"));
+        assert_eq!(term("name~java"), text(Field::Name, Op::Contains, "java"));
+        assert_eq!(term("cmd~--config"), text(Field::Cmd, Op::Contains, "--config"));
+        assert_eq!(term("rss>1K"), number(Field::Mem, Op::Gt, 1024.0));
+    }
+
+    #[test]
+    fn parses_negation_and_bare_words() {
+        assert_eq!(term("!user:root"), Term::Not(Box::new(text(Field::User, Op::Eq, "root"))));
+        assert_eq!(term("!!pid=1"), Term::Not(Box::new(Term::Not(Box::new(number(Field::Pid, Op::Eq, 1.0))))));
+        assert_eq!(term("Java"), Term::Any("java".to_owned()));
+        assert_eq!(term("42"), Term::Any("42".to_owned()));
+    }
+
+    #[test]
+    fn rejects_invalid_terms() {
+        assert_eq!(Term::parse("size>1"), Err("unknown field: size".to_owned()));
+        assert_eq!(Term::parse("cpu~5"), Err("cpu is a number, use :, <, > in cpu~5".to_owned()));
+        assert_eq!(Term::parse("user<root"), Err("user is text, use : or ~ in user<root".to_owned()));
+        assert_eq!(Term::parse("user:"), Err("missing value in user:".to_owned()));
+        assert_eq!(Term::parse("cpu>="), Err("missing value in cpu>=".to_owned()));
+        assert_eq!(Term::parse("cpu>lots"), Err("invalid number in cpu>lots".to_owned()));
+        assert_eq!(Term::parse("mem>1XB"), Err("invalid number in mem>1XB".to_owned()));
+        assert!(Query::parse("user:ci size>1").is_err());
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("512"), Some(512.0));
+        assert_eq!(parse_size("64K"), Some(65536.0));
+        assert_eq!(parse_size("64k"), Some(65536.0));
+        assert_eq!(parse_size("1GiB"), Some(1073741824.0));
+        assert_eq!(parse_size("1GB"), Some(1e9));
+        assert_eq!(parse_size("1.5MB"), Some(1.5e6));
+        assert_eq!(parse_size("2T"), Some(2.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_size("1Q"), None);
+        assert_eq!(parse_size("GB"), None);
+    }
+
+    #[test]
+    fn matches_a_process() {
+        let process = ProcessInfo {
+            pid: 4242,
+            ppid: 1,
+            name: "java".to_owned(),
+            cmdline: "/usr/bin/java -jar build.jar --config ci.toml".to_owned(),
+            uid: 1001,
+            user: "ci".to_owned(),
+            state: "S".to_owned(),
+            threads: 48,
+            rss: 2 * 1024 * 1024 * 1024,
+            nice: 5,
+            ..ProcessInfo::default()
+        };
+        let matches = |query: &str, cpu: f32| Query::parse(query).unwrap().matches(&process, cpu);
+
+        assert!(matches("", 0.0));
+        assert!(matches("user:ci cpu>20 mem>1GiB name~JAVA", 25.0));
+        assert!(!matches("user:ci cpu>20", 20.0));
+        assert!(matches("cpu>=20", 20.0));
+        assert!(matches("4242", 0.0));
+        assert!(matches("build.jar", 0.0));
+        assert!(matches("cmd~--config threads>=48 nice=5 uid:1001 ppid=1", 0.0));
+        assert!(!matches("user:root", 0.0));
+        assert!(matches("!user:root", 0.0));
+        assert!(!matches("!name~jav", 0.0));
+        assert!(!matches("state:s", 0.0));
+        assert!(!matches("mem>3GB", 0.0));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::process::ProcessDetail;
+use super::structs::AllData;
+
+/// One line of a recording: an `AllData` snapshot and the wall-clock time
+/// (milliseconds since the Unix epoch) it was collected at.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp_ms: u64,
+    pub data: AllData,
+    /// Details of the processes open in inspectors, `None` for those that
+    /// are gone. They are only shown live, so they are neither recorded
+    /// nor sent by the agent.
+    #[serde(skip)]
+    pub process_details: HashMap<u32, Option<ProcessDetail>>,
+}
+
+impl Sample {
+
+    pub fn new(data: AllData) -> Sample {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Sample {
+            timestamp_ms,
+            data,
+            process_details: HashMap::new(),
+        }
+    }
+}
+
+/// Appends samples to an NDJSON file, one `Sample` per line.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+
+    pub fn new(path: &Path) -> io::Result<Recorder> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Recorder {
+            writer: BufWriter::new(file)
+        })
+    }
+
+    /// Writes the sample and flushes, so an interrupted session still
+    /// leaves every collected sample on disk.
+    pub fn append(&mut self, sample: &Sample) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, sample)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+pub const REPLAY_SPEEDS: [f32; 6] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+
+/// A recording loaded into memory together with the playback state.
+pub struct Replay {
+    samples: Vec<Sample>,
+    position: usize,
+    playing: bool,
+    speed: f32,
+    cursor_ms: f64,
+    last_tick: Option<Instant>,
+}
+
+impl Replay {
+
+    /// Loads every sample of the file. Lines that fail to parse (e.g. a
+    /// truncated last line) are skipped.
+    pub fn open(path: &Path) -> io::Result<Replay> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut samples: Vec<Sample> = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Ok(sample) = serde_json::from_str::<Sample>(&line) {
+                samples.push(sample);
+            }
+        }
+        if samples.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "recording contains no samples"));
+        }
+        samples.sort_by_key(|s| s.timestamp_ms);
+        Ok(Replay {
+            samples,
+            position: 0,
+            playing: true,
+            speed: 1.0,
+            cursor_ms: 0.0,
+            last_tick: None,
+        })
+    }
+
+    pub fn current(&self) -> &Sample {
+        &self.samples[self.position]
+    }
+
+    /// The sample before the current one, used for rates after a seek.
+    pub fn previous(&self) -> Option<&Sample> {
+        self.position.checked_sub(1).map(|i| &self.samples[i])
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Milliseconds between the first sample and the current one.
+    pub fn elapsed_ms(&self) -> u64 {
+        self.current().timestamp_ms - self.samples[0].timestamp_ms
+    }
+
+    /// Milliseconds between the first and the last sample.
+    pub fn duration_ms(&self) -> u64 {
+        self.samples[self.samples.len() - 1].timestamp_ms - self.samples[0].timestamp_ms
+    }
+
+    pub fn toggle_play(&mut self) {
+        if !self.playing && self.position + 1 == self.samples.len() {
+            self.seek(0);
+        }
+        self.playing = !self.playing;
+        self.last_tick = None;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.samples.len() - 1);
+        self.cursor_ms = self.elapsed_ms() as f64;
+        self.last_tick = None;
+    }
+
+    /// Moves the playback cursor by the wall-clock time since the previous
+    /// tick scaled by the speed. Returns `true` if the current sample changed.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let step = self.last_tick.map(|t| now.duration_since(t)).unwrap_or(Duration::ZERO);
+        self.last_tick = Some(now);
+        self.cursor_ms += step.as_secs_f64() * 1000.0 * self.speed as f64;
+
+        let start = self.samples[0].timestamp_ms;
+        let prev = self.position;
+        while self.position + 1 < self.samples.len()
+            && ((self.samples[self.position + 1].timestamp_ms - start) as f64) <= self.cursor_ms {
+            self.position += 1;
+        }
+        if self.position + 1 == self.samples.len() {
+            self.playing = false;
+        }
+        self.position != prev
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture::Fixture;
+
+    fn sample(timestamp_ms: u64) -> Sample {
+        let mut sample = Sample::new(AllData::default());
+        sample.timestamp_ms = timestamp_ms;
+        sample.data.os_data.uptime = timestamp_ms / 1000;
+        sample
+    }
+
+    /// A recording of samples one second apart, ending in a half-written line.
+    fn recording(fixture: &Fixture, count: u64) -> Replay {
+        let path = fixture.path().join("session.jsonl");
+        let mut recorder = Recorder::new(&path).unwrap();
+        for i in 0..count {
+            recorder.append(&sample(1_000_000 + i * 1000)).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"timestamp_ms":1009000,"data":{"cpu_d"#).unwrap();
+        Replay::open(&path).unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_a_recording() {
+        let fixture = Fixture::new("record-round-trip");
+        let replay = recording(&fixture, 3);
+        assert_eq!(replay.len(), 3);
+        assert_eq!(replay.current(), &sample(1_000_000));
+        assert_eq!(replay.duration_ms(), 2000);
+
+        fixture.write("empty.jsonl", "\n");
+        assert_eq!(Replay::open(&fixture.path().join("empty.jsonl")).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn advances_by_the_scaled_wall_clock() {
+        let fixture = Fixture::new("record-advance");
+        let mut replay = recording(&fixture, 5);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // the first tick only starts the clock
+        assert!(!replay.advance(at(0)));
+        assert!(!replay.advance(at(999)));
+        assert!(replay.advance(at(1000)));
+        assert_eq!((replay.position(), replay.elapsed_ms()), (1, 1000));
+
+        replay.set_speed(2.0);
+        assert!(replay.advance(at(1500)));
+        assert_eq!(replay.position(), 2);
+
+        // paused: the clock doesn't run
+        replay.toggle_play();
+        assert!(!replay.advance(at(5000)));
+        replay.toggle_play();
+        assert!(!replay.advance(at(6000)));
+        assert_eq!(replay.position(), 2);
+
+        // stops at the end
+        assert!(replay.advance(at(7000)));
+        assert_eq!(replay.position(), 4);
+        assert!(!replay.is_playing());
+        assert!(!replay.advance(at(9000)));
+    }
+
+    #[test]
+    fn seeks_and_restarts_from_the_end() {
+        let fixture = Fixture::new("record-seek");
+        let mut replay = recording(&fixture, 5);
+        replay.seek(3);
+        assert_eq!((replay.position(), replay.previous().map(|s| s.timestamp_ms)), (3, Some(1_002_000)));
+        replay.seek(99);
+        assert_eq!(replay.position(), 4);
+        let start = Instant::now();
+        replay.advance(start);
+        assert!(!replay.is_playing());
+
+        // play again at the end: from the start
+        replay.toggle_play();
+        assert!(replay.is_playing());
+        assert_eq!((replay.position(), replay.previous()), (0, None));
+        assert!(!replay.advance(start + Duration::from_millis(10)));
+        assert!(replay.advance(start + Duration::from_millis(1010)));
+        assert_eq!(replay.position(), 1);
+    }
+}
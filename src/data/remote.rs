@@ -0,0 +1,218 @@
+//! Agent mode and the client side of the agent protocol.
+//!
+//! An agent listens on a TCP port and streams newline-delimited JSON frames
+//! to every connected client. Each frame is an object with a `type` field:
+//!
+//! - `{"type": "hello", "version": 1, "host": "<host name>"}` is sent once,
+//!   right after the connection is accepted;
+//! - `{"type": "sample", "timestamp_ms": <u64>, "data": {...}}` is sent
+//!   after every collection; `timestamp_ms` and `data` are the same as in a
+//!   recording line (see `record::Sample`). A client that connects between
+//!   two collections gets the latest sample right after the hello.
+//!
+//! Clients never send anything; the agent drops a client as soon as a write
+//! to it fails or it falls `CLIENT_BACKLOG` samples behind.
+
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::SinkExt;
+use iced::{stream, Subscription};
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::record::Sample;
+use super::watchdog::Watchdog;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7878;
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How many samples may wait for a slow client before it is dropped.
+const CLIENT_BACKLOG: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Frame {
+    Hello { version: u32, host: String },
+    Sample(Box<Sample>),
+}
+
+/// Appends the default agent port to addresses given without one.
+pub fn with_default_port(address: &str) -> String {
+    let has_port = match address.rsplit_once(':') {
+        Some((host, port)) => port.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']')),
+        None => false,
+    };
+    if has_port {
+        address.to_owned()
+    } else if address.contains(':') && !address.starts_with('[') {
+        format!("[{}]:{}", address, DEFAULT_PORT)
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+/// Runs the headless agent: collects `AllData` every `interval` and sends
+/// it to every client connected to `listen`. Only returns on a bind error.
+pub fn run_agent(listen: &str, interval: Duration) -> io::Result<()> {
+    let listener = TcpListener::bind(listen)?;
+    eprintln!("agent listening on {}", listener.local_addr()?);
+
+    let clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>> = Arc::new(Mutex::new(Vec::new()));
+    let latest: Arc<Mutex<Option<Arc<str>>>> = Arc::new(Mutex::new(None));
+
+    let accept_clients = Arc::clone(&clients);
+    let accept_latest = Arc::clone(&latest);
+    thread::spawn(move || {
+        let hello = match encode(&Frame::Hello {
+            version: PROTOCOL_VERSION,
+            host: System::host_name().unwrap_or("unknown".to_owned()),
+        }) {
+            Ok(hello) => hello,
+            Err(e) => {
+                eprintln!("can't encode the hello frame: {}", e);
+                return;
+            }
+        };
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let _ = stream.set_nodelay(true);
+            let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+            // holding `latest` until the client is listed, so every sample
+            // is either in its greeting or sent to it
+            let latest = accept_latest.lock().unwrap();
+            let mut greeting = hello.clone();
+            if let Some(line) = latest.as_ref() {
+                greeting.push_str(line);
+            }
+            let (sender, receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+            thread::spawn(move || write_client(stream, greeting, receiver));
+            accept_clients.lock().unwrap().push(sender);
+        }
+    });
+
+    let mut watchdog = Watchdog::new();
+    loop {
+        match encode(&Frame::Sample(Box::new(Sample::new(watchdog.collect())))) {
+            Ok(line) => {
+                let line: Arc<str> = line.into();
+                let mut latest = latest.lock().unwrap();
+                // a full queue means the client is too slow; dropping its sender ends its writer
+                clients.lock().unwrap().retain(|client| client.try_send(Arc::clone(&line)).is_ok());
+                *latest = Some(line);
+            }
+            // clients keep the last sample they got rather than an empty line
+            Err(e) => eprintln!("can't encode a sample, skipping it: {}", e),
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Sends the greeting and then every queued line to one client, until a
+/// write fails or the agent drops the client.
+fn write_client(mut stream: TcpStream, greeting: String, lines: Receiver<Arc<str>>) {
+    if stream.write_all(greeting.as_bytes()).is_err() {
+        return;
+    }
+    for line in lines {
+        if stream.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn encode(frame: &Frame) -> serde_json::Result<String> {
+    let mut line = serde_json::to_string(frame)?;
+    line.push('\n');
+    Ok(line)
+}
+
+#[derive(Debug, Clone)]
+pub enum RemoteStatus {
+    Connected(String),
+    Sample(Box<Sample>),
+    Disconnected(String),
+}
+
+/// An event received from the agent at `address`.
+#[derive(Debug, Clone)]
+pub struct RemoteEvent {
+    pub address: String,
+    pub status: RemoteStatus,
+}
+
+/// Keeps a connection to the agent at `address` open, reconnecting after
+/// errors, and reports every hello, sample and disconnect.
+pub fn connect(address: String) -> Subscription<RemoteEvent> {
+    Subscription::run_with_id(address.clone(), stream::channel(100, move |mut output| async move {
+        loop {
+            let error = match tokio::net::TcpStream::connect(&address).await {
+                Ok(socket) => {
+                    let mut lines = BufReader::new(socket).lines();
+                    loop {
+                        let status = match lines.next_line().await {
+                            Ok(Some(line)) => match serde_json::from_str::<Frame>(&line) {
+                                Ok(Frame::Hello { version, host }) if version == PROTOCOL_VERSION => RemoteStatus::Connected(host),
+                                Ok(Frame::Hello { version, .. }) => break format!("unsupported protocol version {}", version),
+                                Ok(Frame::Sample(sample)) => RemoteStatus::Sample(sample),
+                                Err(e) => break format!("invalid frame: {}", e),
+                            },
+                            Ok(None) => break "connection closed".to_owned(),
+                            Err(e) => break e.to_string(),
+                        };
+                        let _ = output.send(RemoteEvent { address: address.clone(), status }).await;
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+            let _ = output.send(RemoteEvent { address: address.clone(), status: RemoteStatus::Disconnected(error) }).await;
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::structs::AllData;
+
+    #[test]
+    fn frames_are_json_lines() {
+        let hello = encode(&Frame::Hello { version: PROTOCOL_VERSION, host: "db1".to_owned() }).unwrap();
+        assert_eq!(hello, "{\"type\":\"hello\",\"version\":1,\"host\":\"db1\"}\n");
+        assert_eq!(serde_json::from_str::<Frame>(&hello).unwrap(), Frame::Hello { version: 1, host: "db1".to_owned() });
+
+        let mut sample = Sample::new(AllData::default());
+        sample.timestamp_ms = 1_700_000_000_000;
+        sample.data.os_data.uptime = 3600;
+        let line = encode(&Frame::Sample(Box::new(sample.clone()))).unwrap();
+        assert_eq!(line.matches('\n').count(), 1);
+        assert!(line.ends_with('\n'));
+
+        // the sample's fields sit next to `type`, as in a recording line
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["type"], "sample");
+        assert_eq!(value["timestamp_ms"], 1_700_000_000_000u64);
+        assert_eq!(value["data"]["os_data"]["uptime"], 3600);
+        assert_eq!(serde_json::from_str::<Frame>(&line).unwrap(), Frame::Sample(Box::new(sample)));
+
+        assert!(serde_json::from_str::<Frame>("{\"type\":\"goodbye\"}").is_err());
+    }
+
+    #[test]
+    fn adds_the_default_port() {
+        assert_eq!(with_default_port("db1"), "db1:7878");
+        assert_eq!(with_default_port("db1:9000"), "db1:9000");
+        assert_eq!(with_default_port("10.0.0.2"), "10.0.0.2:7878");
+        assert_eq!(with_default_port("::1"), "[::1]:7878");
+        assert_eq!(with_default_port("fe80::1:2"), "[fe80::1:2]:7878");
+        assert_eq!(with_default_port("[::1]"), "[::1]:7878");
+        assert_eq!(with_default_port("[::1]:9000"), "[::1]:9000");
+    }
+}
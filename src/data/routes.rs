@@ -0,0 +1,273 @@
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+pub const PROC_NET_ROUTE: &str = "/proc/net/route";
+pub const PROC_NET_IPV6_ROUTE: &str = "/proc/net/ipv6_route";
+pub const PROC_NET_ARP: &str = "/proc/net/arp";
+pub const RESOLV_CONF: &str = "/etc/resolv.conf";
+pub const ETC_HOSTS: &str = "/etc/hosts";
+
+/// Route flags from `linux/route.h` and `linux/ipv6_route.h`.
+const RTF_UP: u32 = 0x0001;
+const RTF_REJECT: u32 = 0x0200;
+const RTF_LOCAL: u32 = 0x8000_0000;
+
+/// ARP entry flag of a resolved neighbour.
+const ATF_COM: u32 = 0x02;
+
+/// One route of the main table. `gateway` is `None` for directly
+/// connected networks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    pub destination: IpAddr,
+    pub prefix: u8,
+    pub gateway: Option<IpAddr>,
+    pub interface: String,
+    pub metric: u32,
+    pub up: bool,
+}
+
+impl Route {
+
+    pub fn is_default(&self) -> bool {
+        self.prefix == 0
+    }
+
+    /// Parses a `/proc/net/route` line; addresses are hex in host byte order:
+    /// `eth0 00000000 010200C0 0003 0 0 0 00000000 0 0 0` (tab separated).
+    pub fn parse_v4(line: &str) -> Option<Route> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            return None;
+        }
+        let address = |hex: &str| u32::from_str_radix(hex, 16).ok().map(|a| Ipv4Addr::from(a.to_ne_bytes()));
+        let gateway = address(fields[2])?;
+        let flags = u32::from_str_radix(fields[3], 16).ok()?;
+        Some(Route {
+            destination: IpAddr::V4(address(fields[1])?),
+            prefix: u32::from_str_radix(fields[7], 16).ok()?.count_ones() as u8,
+            gateway: Some(IpAddr::V4(gateway)).filter(|_| !gateway.is_unspecified()),
+            interface: fields[0].to_owned(),
+            metric: fields[6].parse().ok()?,
+            up: flags & RTF_UP != 0,
+        })
+    }
+
+    /// Parses a `/proc/net/ipv6_route` line: destination, prefix, source,
+    /// source prefix, next hop, metric, refcount, use, flags and device, all
+    /// hex in network byte order. Local and reject routes give `None`.
+    pub fn parse_v6(line: &str) -> Option<Route> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+        let flags = u32::from_str_radix(fields[8], 16).ok()?;
+        if flags & (RTF_REJECT | RTF_LOCAL) != 0 {
+            return None;
+        }
+        let address = |hex: &str| u128::from_str_radix(hex, 16).ok().map(Ipv6Addr::from);
+        let gateway = address(fields[4])?;
+        Some(Route {
+            destination: IpAddr::V6(address(fields[0])?),
+            prefix: u8::from_str_radix(fields[1], 16).ok()?,
+            gateway: Some(IpAddr::V6(gateway)).filter(|_| !gateway.is_unspecified()),
+            interface: fields[9].to_owned(),
+            metric: u32::from_str_radix(fields[5], 16).ok()?,
+            up: flags & RTF_UP != 0,
+        })
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_default() {
+            write!(f, "default")?;
+        } else {
+            write!(f, "{}/{}", self.destination, self.prefix)?;
+        }
+        if let Some(gateway) = &self.gateway {
+            write!(f, " via {}", gateway)?;
+        }
+        write!(f, " dev {} metric {}", self.interface, self.metric)
+    }
+}
+
+/// One neighbour of `/proc/net/arp`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArpEntry {
+    pub address: IpAddr,
+    pub mac: String,
+    pub interface: String,
+    pub complete: bool,
+}
+
+impl ArpEntry {
+
+    /// `192.0.2.1        0x1         0x2         02:fc:00:00:00:05     *        eth0`
+    pub fn parse(line: &str) -> Option<ArpEntry> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            return None;
+        }
+        let flags = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).ok()?;
+        Some(ArpEntry {
+            address: fields[0].parse().ok()?,
+            mac: fields[3].to_owned(),
+            interface: fields[5].to_owned(),
+            complete: flags & ATF_COM != 0,
+        })
+    }
+}
+
+/// The `nameserver`, `search`/`domain` and `options` lines of `resolv.conf`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resolver {
+    pub nameservers: Vec<String>,
+    pub search: Vec<String>,
+    pub options: Vec<String>,
+}
+
+impl Resolver {
+
+    pub fn parse(text: &str) -> Resolver {
+        let mut resolver = Resolver::default();
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("nameserver") => resolver.nameservers.extend(fields.next().map(|f| f.to_owned())),
+                // the last `search` or `domain` line wins
+                Some("search") | Some("domain") => resolver.search = fields.map(|f| f.to_owned()).collect(),
+                Some("options") => resolver.options.extend(fields.map(|f| f.to_owned())),
+                _ => {}
+            }
+        }
+        resolver
+    }
+}
+
+/// An `/etc/hosts` line: an address and its names.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostsEntry {
+    pub address: String,
+    pub names: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingData {
+    pub routes: Vec<Route>,
+    pub arp: Vec<ArpEntry>,
+    pub resolver: Resolver,
+    pub hosts: Vec<HostsEntry>,
+}
+
+impl RoutingData {
+
+    pub fn new() -> RoutingData {
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+        let mut routes: Vec<Route> = read(PROC_NET_ROUTE).lines().skip(1).filter_map(Route::parse_v4).collect();
+        routes.extend(read(PROC_NET_IPV6_ROUTE).lines().filter_map(Route::parse_v6));
+        RoutingData {
+            routes,
+            arp: read(PROC_NET_ARP).lines().skip(1).filter_map(ArpEntry::parse).collect(),
+            resolver: Resolver::parse(&read(RESOLV_CONF)),
+            hosts: parse_hosts(&read(ETC_HOSTS)),
+        }
+    }
+
+    pub fn default_gateways(&self) -> impl Iterator<Item = &Route> {
+        self.routes.iter().filter(|r| r.is_default() && r.gateway.is_some())
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Routing data***");
+        for route in &self.routes {
+            println!("{}", route);
+        }
+        for entry in &self.arp {
+            println!("{}............{} {}", entry.address, entry.mac, entry.interface);
+        }
+        println!("nameservers............{:?}", self.resolver.nameservers);
+        println!("search............{:?}", self.resolver.search);
+    }
+}
+
+fn parse_hosts(text: &str) -> Vec<HostsEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut fields = line.split_whitespace();
+            Some(HostsEntry {
+                address: fields.next()?.to_owned(),
+                names: fields.map(|f| f.to_owned()).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // /proc/net/route prints addresses as host-order words.
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parses_ipv4_routes() {
+        let default = Route::parse_v4("eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0").unwrap();
+        assert!(default.is_default());
+        assert_eq!(default.gateway, Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+        assert_eq!(default.metric, 100);
+        assert!(default.up);
+        assert_eq!(default.to_string(), "default via 192.168.1.1 dev eth0 metric 100");
+
+        let network = Route::parse_v4("eth0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0").unwrap();
+        assert_eq!(network.destination, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 0)));
+        assert_eq!(network.prefix, 24);
+        assert_eq!(network.gateway, None);
+        assert_eq!(network.to_string(), "192.168.1.0/24 dev eth0 metric 100");
+
+        assert!(Route::parse_v4("Iface\tDestination\tGateway").is_none());
+    }
+
+    #[test]
+    fn parses_ipv6_routes() {
+        let network = Route::parse_v6("20010db8000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0").unwrap();
+        assert_eq!(network.destination, "2001:db8::".parse::<IpAddr>().unwrap());
+        assert_eq!(network.prefix, 64);
+        assert_eq!(network.gateway, None);
+        assert_eq!(network.metric, 256);
+        assert_eq!(network.interface, "eth0");
+
+        let default = Route::parse_v6("00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0").unwrap();
+        assert!(default.is_default());
+        assert_eq!(default.gateway, Some("fe80::1".parse::<IpAddr>().unwrap()));
+        assert_eq!(default.to_string(), "default via fe80::1 dev eth0 metric 1024");
+    }
+
+    #[test]
+    fn skips_local_and_reject_ipv6_routes() {
+        let local = "00000000000000000000000000000001 80 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000002 00000000 80200001       lo";
+        let reject = "00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo";
+        assert_eq!(Route::parse_v6(local), None);
+        assert_eq!(Route::parse_v6(reject), None);
+    }
+
+    #[test]
+    fn parses_arp_and_resolver() {
+        let entry = ArpEntry::parse("192.0.2.1        0x1         0x2         02:fc:00:00:00:05     *        eth0").unwrap();
+        assert_eq!(entry.address, "192.0.2.1".parse::<IpAddr>().unwrap());
+        assert_eq!(entry.mac, "02:fc:00:00:00:05");
+        assert!(entry.complete);
+
+        let resolver = Resolver::parse("# comment\nnameserver 127.0.0.53\nnameserver ::1\nsearch a.example\nsearch b.example c.example\noptions edns0 trust-ad\n");
+        assert_eq!(resolver.nameservers, ["127.0.0.53", "::1"]);
+        assert_eq!(resolver.search, ["b.example", "c.example"]);
+        assert_eq!(resolver.options, ["edns0", "trust-ad"]);
+    }
+}
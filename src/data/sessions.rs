@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+pub const UTMP: &str = "/run/utmp";
+pub const WTMP: &str = "/var/log/wtmp";
+
+/// How many logins and reboots of wtmp are kept, newest first.
+pub const HISTORY_LEN: usize = 30;
+
+/// How many records are read back from the end of wtmp for the history;
+/// wtmp is never rotated on some systems and grows to many megabytes.
+pub const WTMP_TAIL: u64 = 4096;
+
+static HISTORY: LazyLock<Mutex<Option<CachedHistory>>> = LazyLock::new(Mutex::default);
+
+/// The history last read from wtmp, with the size and modification time
+/// wtmp had then; it's only read again once either changes.
+struct CachedHistory {
+    len: u64,
+    modified: SystemTime,
+    history: Vec<LoginRecord>,
+}
+
+/// The glibc `struct utmp` layout on Linux, the same on 32 and 64 bit.
+const RECORD_SIZE: usize = 384;
+const BOOT_TIME: i16 = 2;
+const USER_PROCESS: i16 = 7;
+const DEAD_PROCESS: i16 = 8;
+
+/// One record of utmp/wtmp with the fields we use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Record {
+    kind: i16,
+    pid: i32,
+    line: String,
+    user: String,
+    host: String,
+    time: u64,
+}
+
+impl Record {
+
+    fn parse(bytes: &[u8]) -> Record {
+        let string = |range: std::ops::Range<usize>| {
+            let field = &bytes[range];
+            let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+        let i32_at = |at: usize| i32::from_ne_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        Record {
+            kind: i16::from_ne_bytes([bytes[0], bytes[1]]),
+            pid: i32_at(4),
+            line: string(8..40),
+            user: string(44..76),
+            host: string(76..332),
+            time: i32_at(340) as u32 as u64,
+        }
+    }
+}
+
+fn read_records(path: &Path) -> Vec<Record> {
+    fs::read(path)
+        .map(|bytes| bytes.chunks_exact(RECORD_SIZE).map(Record::parse).collect())
+        .unwrap_or_default()
+}
+
+/// The last `count` whole records of the file.
+fn read_last_records(file: &mut File, len: u64, count: u64) -> std::io::Result<Vec<Record>> {
+    let records = len / RECORD_SIZE as u64;
+    file.seek(SeekFrom::Start(records.saturating_sub(count) * RECORD_SIZE as u64))?;
+    let mut bytes = Vec::new();
+    file.take(count.min(records) * RECORD_SIZE as u64).read_to_end(&mut bytes)?;
+    Ok(bytes.chunks_exact(RECORD_SIZE).map(Record::parse).collect())
+}
+
+/// `login_history` of the tail of wtmp, reused while wtmp is unchanged.
+fn wtmp_history(path: &Path) -> Vec<LoginRecord> {
+    let Ok(mut file) = File::open(path) else { return Vec::new() };
+    let Ok(metadata) = file.metadata() else { return Vec::new() };
+    let (len, modified) = (metadata.len(), metadata.modified().unwrap_or(UNIX_EPOCH));
+    let mut cache = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.as_ref().filter(|c| c.len == len && c.modified == modified) {
+        return cached.history.clone();
+    }
+    let history = login_history(&read_last_records(&mut file, len, WTMP_TAIL).unwrap_or_default());
+    *cache = Some(CachedHistory { len, modified, history: history.clone() });
+    history
+}
+
+/// A user logged in right now. Times are seconds since the Unix epoch;
+/// `idle_secs` is how long ago the terminal was last written to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub user: String,
+    pub tty: String,
+    pub host: String,
+    pub pid: i32,
+    pub login_time: u64,
+    pub idle_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoginKind {
+    #[default]
+    Login,
+    Reboot,
+}
+
+/// A past login or reboot from wtmp; `logout_time` is `None` while the
+/// session is still open.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoginRecord {
+    pub kind: LoginKind,
+    pub user: String,
+    pub tty: String,
+    pub host: String,
+    pub login_time: u64,
+    pub logout_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionsData {
+    pub sessions: Vec<Session>,
+    pub history: Vec<LoginRecord>,
+    /// False when utmp is missing, as on systems tracking logins in logind only.
+    pub available: bool,
+}
+
+impl SessionsData {
+
+    pub fn new() -> SessionsData {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let utmp = Path::new(UTMP);
+        let sessions = read_records(utmp).into_iter()
+            .filter(|r| r.kind == USER_PROCESS && !r.user.is_empty())
+            .map(|r| Session {
+                idle_secs: idle_secs(&r.line, now),
+                user: r.user,
+                tty: r.line,
+                host: r.host,
+                pid: r.pid,
+                login_time: r.time,
+            })
+            .collect();
+        SessionsData {
+            sessions,
+            history: wtmp_history(Path::new(WTMP)),
+            available: utmp.exists(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Sessions data***");
+        for s in &self.sessions {
+            println!("{}............{} {} since {} idle {:?}", s.user, s.tty, s.host, s.login_time, s.idle_secs);
+        }
+        for l in &self.history {
+            println!("{:?} {}............{} {} {} - {:?}", l.kind, l.user, l.tty, l.host, l.login_time, l.logout_time);
+        }
+    }
+}
+
+/// Pairs every login with the next `DEAD_PROCESS` on its tty (or the next
+/// boot, which ends every session), walking back from the end of wtmp until
+/// `HISTORY_LEN` entries are found. The latest come first.
+fn login_history(records: &[Record]) -> Vec<LoginRecord> {
+    let mut history: Vec<LoginRecord> = Vec::new();
+    let mut logouts: HashMap<&str, u64> = HashMap::new();
+    let mut next_boot: Option<u64> = None;
+    for record in records.iter().rev() {
+        if history.len() == HISTORY_LEN {
+            break;
+        }
+        match record.kind {
+            DEAD_PROCESS => {
+                logouts.insert(&record.line, record.time);
+            }
+            USER_PROCESS if !record.user.is_empty() => history.push(LoginRecord {
+                kind: LoginKind::Login,
+                user: record.user.clone(),
+                tty: record.line.clone(),
+                host: record.host.clone(),
+                login_time: record.time,
+                logout_time: logouts.remove(record.line.as_str()).or(next_boot),
+            }),
+            BOOT_TIME => {
+                history.push(LoginRecord {
+                    kind: LoginKind::Reboot,
+                    user: "reboot".to_owned(),
+                    tty: "system boot".to_owned(),
+                    host: record.host.clone(),
+                    login_time: record.time,
+                    logout_time: next_boot,
+                });
+                logouts.clear();
+                next_boot = Some(record.time);
+            }
+            _ => {}
+        }
+    }
+    history
+}
+
+/// Seconds since the terminal's device was last accessed, like `who -u`.
+fn idle_secs(tty: &str, now: u64) -> Option<u64> {
+    let accessed = fs::metadata(Path::new("/dev").join(tty)).and_then(|m| m.accessed()).ok()?;
+    let accessed = accessed.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(now.saturating_sub(accessed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::data::fixture::Fixture;
+
+    fn record(kind: i16, line: &str, user: &str, time: i32) -> Vec<u8> {
+        let mut bytes = vec![0; RECORD_SIZE];
+        bytes[0..2].copy_from_slice(&kind.to_ne_bytes());
+        bytes[8..8 + line.len()].copy_from_slice(line.as_bytes());
+        bytes[44..44 + user.len()].copy_from_slice(user.as_bytes());
+        bytes[340..344].copy_from_slice(&time.to_ne_bytes());
+        bytes
+    }
+
+    #[test]
+    fn reads_the_last_records() {
+        let fixture = Fixture::new("wtmp");
+        let path = fixture.path().join("wtmp");
+        let mut file = File::create(&path).unwrap();
+        for time in 0..10 {
+            file.write_all(&record(USER_PROCESS, "pts/0", "alice", time)).unwrap();
+        }
+        // a record cut short by a crash while it was written
+        file.write_all(&[0; 100]).unwrap();
+        drop(file);
+
+        let mut file = File::open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        let times = |records: Vec<Record>| records.iter().map(|r| r.time).collect::<Vec<_>>();
+        assert_eq!(times(read_last_records(&mut file, len, 3).unwrap()), [7, 8, 9]);
+        assert_eq!(times(read_last_records(&mut file, len, 20).unwrap()).len(), 10);
+    }
+
+    #[test]
+    fn pairs_logins_with_logouts_and_boots() {
+        let bytes: Vec<u8> = [
+            record(BOOT_TIME, "~", "reboot", 100),
+            record(USER_PROCESS, "tty1", "alice", 110),
+            record(USER_PROCESS, "pts/0", "bob", 120),
+            record(DEAD_PROCESS, "pts/0", "", 130),
+            record(BOOT_TIME, "~", "reboot", 200),
+            record(USER_PROCESS, "tty1", "alice", 210),
+        ].concat();
+        let records: Vec<Record> = bytes.chunks_exact(RECORD_SIZE).map(Record::parse).collect();
+        let history = login_history(&records);
+        let history: Vec<(&str, u64, Option<u64>)> = history.iter()
+            .map(|l| (l.user.as_str(), l.login_time, l.logout_time))
+            .collect();
+        assert_eq!(history, [
+            ("alice", 210, None),
+            ("reboot", 200, None),
+            ("bob", 120, Some(130)),
+            ("alice", 110, Some(200)),
+            ("reboot", 100, Some(200)),
+        ]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+pub const PROTOCOLS: [&str; 4] = ["tcp", "tcp6", "udp", "udp6"];
+
+pub const STATES: [&str; 13] = [
+    "ESTABLISHED", "SYN_SENT", "SYN_RECV", "FIN_WAIT1", "FIN_WAIT2", "TIME_WAIT", "CLOSE",
+    "CLOSE_WAIT", "LAST_ACK", "LISTEN", "CLOSING", "NEW_SYN_RECV", "UNCONN",
+];
+
+/// One line of `/proc/net/{tcp,tcp6,udp,udp6}`. `pid` and `process` are only
+/// known for sockets of processes whose `/proc/<pid>/fd` we may read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketEntry {
+    pub protocol: String,
+    pub local_address: IpAddr,
+    pub local_port: u16,
+    pub remote_address: IpAddr,
+    pub remote_port: u16,
+    pub state: String,
+    pub tx_queue: u64,
+    pub rx_queue: u64,
+    pub uid: u32,
+    pub inode: u64,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+impl SocketEntry {
+
+    pub fn local(&self) -> String {
+        format_endpoint(&self.local_address, self.local_port)
+    }
+
+    pub fn remote(&self) -> String {
+        format_endpoint(&self.remote_address, self.remote_port)
+    }
+
+    /// Parses a socket line, e.g.
+    /// `0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000 1000 0 21234 ...`
+    pub fn parse(protocol: &str, line: &str) -> Option<SocketEntry> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+        let (local_address, local_port) = parse_endpoint(fields[1])?;
+        let (remote_address, remote_port) = parse_endpoint(fields[2])?;
+        let (tx_queue, rx_queue) = fields[4].split_once(':')?;
+        Some(SocketEntry {
+            protocol: protocol.to_owned(),
+            local_address,
+            local_port,
+            remote_address,
+            remote_port,
+            state: state_name(protocol, fields[3]).to_owned(),
+            tx_queue: u64::from_str_radix(tx_queue, 16).ok()?,
+            rx_queue: u64::from_str_radix(rx_queue, 16).ok()?,
+            uid: fields[7].parse().ok()?,
+            inode: fields[9].parse().ok()?,
+            pid: None,
+            process: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocketsData {
+    pub sockets: Vec<SocketEntry>,
+}
+
+impl SocketsData {
+
+    pub fn new() -> SocketsData {
+        let mut sockets: Vec<SocketEntry> = Vec::new();
+        for protocol in PROTOCOLS {
+            let text = fs::read_to_string(format!("/proc/net/{}", protocol)).unwrap_or_default();
+            sockets.extend(text.lines().skip(1).filter_map(|line| SocketEntry::parse(protocol, line)));
+        }
+
+        let owners = socket_owners();
+        for socket in &mut sockets {
+            if let Some((pid, name)) = owners.get(&socket.inode) {
+                socket.pid = Some(*pid);
+                socket.process = Some(name.clone());
+            }
+        }
+        sockets.sort_by(|a, b| (&a.protocol, a.local_port).cmp(&(&b.protocol, b.local_port)));
+        SocketsData { sockets }
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Sockets data***");
+        for s in &self.sockets {
+            println!("{:<5} {:<45} {:<45} {:<12} {:?} {:?}", s.protocol, s.local(), s.remote(), s.state, s.pid, s.process);
+        }
+    }
+}
+
+/// Maps socket inodes to the pid and name of a process holding them, by
+/// reading the `socket:[inode]` links in every readable `/proc/<pid>/fd`.
+fn socket_owners() -> HashMap<u64, (u32, String)> {
+    let mut owners: HashMap<u64, (u32, String)> = HashMap::new();
+    let Ok(procs) = fs::read_dir("/proc") else { return owners };
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else { continue };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else { continue };
+        let name = fs::read_to_string(entry.path().join("comm")).unwrap_or_default().trim().to_owned();
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else { continue };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|t| t.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse::<u64>() {
+                    owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+                }
+            }
+        }
+    }
+    owners
+}
+
+/// Parses `0100007F:0CEA` (IPv4) or the 32 digit IPv6 form. The kernel
+/// prints the address as 32-bit words in host byte order.
+fn parse_endpoint(text: &str) -> Option<(IpAddr, u16)> {
+    let (address, port) = text.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let address = match address.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(u32::from_str_radix(address, 16).ok()?.to_ne_bytes())),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for i in 0..4 {
+                let word = u32::from_str_radix(&address[i * 8..i * 8 + 8], 16).ok()?;
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+fn state_name(protocol: &str, code: &str) -> &'static str {
+    let index = usize::from_str_radix(code, 16).unwrap_or(0);
+    if protocol.starts_with("udp") && index == 7 {
+        return "UNCONN";
+    }
+    match index {
+        1..=12 => STATES[index - 1],
+        _ => "UNKNOWN",
+    }
+}
+
+fn format_endpoint(address: &IpAddr, port: u16) -> String {
+    match address {
+        IpAddr::V4(a) => format!("{}:{}", a, port),
+        IpAddr::V6(a) => format!("[{}]:{}", a, port),
+    }
+}
+
+// The addresses in /proc/net are printed as host-order words, so these
+// samples taken on x86 only read back right on little-endian machines.
+#[cfg(all(test, target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_tcp_line() {
+        let line = "   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 18923 1 0000000000000000 100 0 0 10 0";
+        let socket = SocketEntry::parse("tcp", line).unwrap();
+        assert_eq!(socket.local_address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(socket.local_port, 631);
+        assert_eq!(socket.remote_address, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(socket.remote_port, 0);
+        assert_eq!(socket.state, "LISTEN");
+        assert_eq!(socket.uid, 0);
+        assert_eq!(socket.inode, 18923);
+        assert_eq!(socket.local(), "127.0.0.1:631");
+    }
+
+    #[test]
+    fn parses_a_tcp6_line() {
+        let line = "   3: B80D0120000000000000000001000000:0016 B80D0120000000000000000002000000:C822 01 00000010:00000020 02:0000152A 00000000  1000        0 51312 4 0000000000000000 20 4 31 10 -1";
+        let socket = SocketEntry::parse("tcp6", line).unwrap();
+        assert_eq!(socket.local_address, "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(socket.local_port, 22);
+        assert_eq!(socket.remote_address, "2001:db8::2".parse::<IpAddr>().unwrap());
+        assert_eq!(socket.remote_port, 51234);
+        assert_eq!(socket.state, "ESTABLISHED");
+        assert_eq!(socket.tx_queue, 16);
+        assert_eq!(socket.rx_queue, 32);
+        assert_eq!(socket.uid, 1000);
+    }
+
+    #[test]
+    fn parses_an_unconnected_udp_line() {
+        let line = "  512: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 16021 2 0000000000000000 0";
+        let socket = SocketEntry::parse("udp", line).unwrap();
+        assert_eq!(socket.local_address, "127.0.0.53".parse::<IpAddr>().unwrap());
+        assert_eq!(socket.local_port, 53);
+        assert_eq!(socket.state, "UNCONN");
+    }
+
+    #[test]
+    fn rejects_malformed_endpoints() {
+        assert_eq!(parse_endpoint("0100007F"), None);
+        assert_eq!(parse_endpoint("01007F:0277"), None);
+        assert_eq!(parse_endpoint("0100007G:0277"), None);
+        assert!(SocketEntry::parse("tcp", "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode").is_none());
+    }
+}
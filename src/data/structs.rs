@@ -2,13 +2,30 @@ use std::collections::HashMap;
 use gfx_backend_vulkan as back_v;
 use gfx_hal::Instance;
 use systemstat::Platform as _;
+use serde::{Deserialize, Serialize};
+
+use super::blockdev::BlockData;
+use super::cgroup::CgroupData;
+use super::filesystem::FsStats;
+use super::hwmon::HwmonData;
+use super::kernel::KernelData;
+use super::link::{InterfaceAddress, LinkInfo};
+use super::pressure::PressureData;
+use super::process::ProcessesData;
+use super::routes::RoutingData;
+use super::sessions::SessionsData;
+use super::sockets::SocketsData;
+use super::summary::percent;
+use super::watchdog::Degraded;
+use super::wireless::WirelessData;
 
 
 use sysinfo::{
     Disks, System, Networks, Components
 };
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CpuData {
    pub count_physical_cores: usize,
    pub count_logical_cores: usize,
@@ -17,6 +34,8 @@ pub struct CpuData {
    pub global_cpu_usage: i32,
    pub speed: u64,
    pub cpu_temp: i32,
+   pub total_times: CpuTimes,
+   pub core_times: Vec<CpuTimes>,
 }
 
 impl CpuData {
@@ -25,6 +44,7 @@ impl CpuData {
         let z = systemstat::System::new();
         let mut sys = System::new_all();
         sys.refresh_cpu_all();
+        let (total_times, core_times) = CpuTimes::read_stat();
         let data: CpuData;
         let mut vec_fr: Vec<u64> = vec![];
         if sys.cpus().len() > 0 {
@@ -35,7 +55,9 @@ impl CpuData {
                 cpu_arch: sysinfo::System::cpu_arch(),
                 global_cpu_usage: (sys.global_cpu_usage()) as i32,
                 speed: sys_info::cpu_speed().unwrap_or(0),
-                cpu_temp: ((z.cpu_temp().unwrap_or(0.0)) as i32)
+                cpu_temp: ((z.cpu_temp().unwrap_or(0.0)) as i32),
+                total_times,
+                core_times
             };
         } else {
             vec_fr.push(0);
@@ -46,12 +68,29 @@ impl CpuData {
                 cpu_arch: sysinfo::System::cpu_arch(),
                 global_cpu_usage: 0,
                 speed: 0,
-                cpu_temp: 0
+                cpu_temp: 0,
+                total_times,
+                core_times
             };
         }
         data
     }
 
+    /// Clock ticks that passed on one CPU since `prev`, the time base of
+    /// per-process CPU usage.
+    pub fn elapsed_ticks(&self, prev: &CpuData) -> f64 {
+        let total = |times: &CpuTimes| times.values().iter().sum::<u64>();
+        let cores = self.core_times.len().max(1) as f64;
+        total(&self.total_times).saturating_sub(total(&prev.total_times)) as f64 / cores
+    }
+
+    /// Busy share (in %) of the whole machine between `prev` and this
+    /// sample, from the `/proc/stat` counters. `None` without the counters.
+    pub fn usage_since(&self, prev: &CpuData) -> Option<f32> {
+        let shares = self.total_times.shares_since(&prev.total_times)?;
+        Some(100.0 - shares[CpuTimes::IDLE] - shares[CpuTimes::IOWAIT])
+    }
+
     #[allow(dead_code)]
     pub fn print_data(&self) {
         println!("**CPU data***");
@@ -71,23 +110,102 @@ impl CpuData {
 
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The jiffies a CPU (or all of them) spent in every state, as counted in
+/// the `cpu` lines of `/proc/stat`. Guest time is already part of `user`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+
+    pub const NAMES: [&'static str; 8] = ["user", "nice", "system", "idle", "iowait", "irq", "softirq", "steal"];
+    pub const IDLE: usize = 3;
+    pub const IOWAIT: usize = 4;
+
+    /// Returns the `cpu` total and the `cpuN` lines of `/proc/stat`.
+    pub fn read_stat() -> (CpuTimes, Vec<CpuTimes>) {
+        CpuTimes::parse_stat(&std::fs::read_to_string("/proc/stat").unwrap_or_default())
+    }
+
+    pub fn parse_stat(text: &str) -> (CpuTimes, Vec<CpuTimes>) {
+        let mut total = CpuTimes::default();
+        let mut cores: Vec<CpuTimes> = Vec::new();
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else { continue };
+            if !name.starts_with("cpu") {
+                continue;
+            }
+            let v: Vec<u64> = fields.map(|f| f.parse().unwrap_or(0)).collect();
+            let get = |i: usize| v.get(i).copied().unwrap_or(0);
+            let times = CpuTimes {
+                user: get(0),
+                nice: get(1),
+                system: get(2),
+                idle: get(3),
+                iowait: get(4),
+                irq: get(5),
+                softirq: get(6),
+                steal: get(7),
+            };
+            if name == "cpu" {
+                total = times;
+            } else {
+                cores.push(times);
+            }
+        }
+        (total, cores)
+    }
+
+    pub fn values(&self) -> [u64; 8] {
+        [self.user, self.nice, self.system, self.idle, self.iowait, self.irq, self.softirq, self.steal]
+    }
+
+    /// The share (in %) of every state in the time elapsed since `prev`, in
+    /// the order of `NAMES`. `None` if no time has passed.
+    pub fn shares_since(&self, prev: &CpuTimes) -> Option<[f32; 8]> {
+        let now = self.values();
+        let before = prev.values();
+        let mut delta = [0u64; 8];
+        for i in 0..8 {
+            delta[i] = now[i].saturating_sub(before[i]);
+        }
+        let total: u64 = delta.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        Some(delta.map(|d| d as f32 * 100.0 / total as f32))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GpuData {
     pub gpu_data_vulcan: Vec<String>,
 }
 
 impl GpuData {
 
-    pub fn new() -> GpuData {
-        let instance: gfx_backend_vulkan::Instance = back_v::Instance::create("monitoring", 1).unwrap();
+    pub fn new() -> Result<GpuData, String> {
+        let instance: gfx_backend_vulkan::Instance = back_v::Instance::create("monitoring", 1)
+            .map_err(|e| format!("no Vulkan backend: {:?}", e))?;
         let adapters  = instance.enumerate_adapters();
         let mut names_v: Vec<String> = Vec::new();
         for adapter in adapters {
             names_v.push(adapter.info.name.to_string());
         }
-        GpuData { 
+        Ok(GpuData {
             gpu_data_vulcan: names_v
-        }
+        })
     }
     
     #[allow(dead_code)]
@@ -100,12 +218,15 @@ impl GpuData {
 
     #[allow(dead_code)]
     pub fn update_gpu(&mut self) {
-        *self = Self::new();
+        if let Ok(gpu) = Self::new() {
+            *self = gpu;
+        }
     }
 
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Disk {
     pub available_space: u64,
     pub disk_type: String,
@@ -113,19 +234,19 @@ pub struct Disk {
     pub total_space: u64,
     pub is_removable: bool,
     pub is_read_only: bool,
+    pub mount_point: String,
+    pub stats: Option<FsStats>,
 }
 
 impl Disk {
 
-    pub fn to_string(&self) -> String {
-        "available_space: ".to_owned() + &(self.available_space.to_string()) + 
-        "\n" + 
-        "disk type: " + &(self.disk_type) +
-        "\n" + 
-        "dfile system: " + &(self.file_system) +
-        "\n" + 
-        "total space: " + &(self.total_space.to_string())
-        
+    /// Used space in %.
+    pub fn used_percent(&self) -> f32 {
+        percent(self.total_space - self.available_space.min(self.total_space), self.total_space)
+    }
+
+    pub fn inode_percent(&self) -> Option<f32> {
+        self.stats.as_ref().and_then(|s| s.inode_percent())
     }
 
     #[allow(dead_code)]
@@ -136,10 +257,18 @@ impl Disk {
         println!("total_space:..............{} Mb", self.total_space / 1024000);
         println!("is_removable:.............{} ", self.is_removable);
         println!("is_read_only:.............{} ", self.is_read_only);
+        println!("mount_point:..............{} ", self.mount_point);
+        if let Some(stats) = &self.stats {
+            println!("inodes:...................{} used of {}", stats.used_inodes(), stats.total_inodes);
+            println!("block_size:...............{} ", stats.block_size);
+            println!("max_name_length:..........{} ", stats.max_name_length);
+            println!("reserved:.................{} Mb", stats.reserved_bytes / 1024000);
+        }
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DramData {
     pub disks: HashMap<String, Disk>
 }
@@ -156,7 +285,9 @@ impl DramData {
                     file_system: disk.file_system().to_str().unwrap_or("unknown").to_owned(), 
                     total_space: disk.total_space() / 1024000,
                     is_removable: disk.is_removable(),
-                    is_read_only: disk.is_read_only()
+                    is_read_only: disk.is_read_only(),
+                    mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                    stats: FsStats::read(disk.mount_point())
                 };
                 map_disk.insert(disk.name().to_str().unwrap_or("unknown").to_owned(), di);
             }
@@ -184,7 +315,8 @@ impl DramData {
 
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RamData {
     pub total_memory: u64,
     pub used_memory: u64,
@@ -192,6 +324,7 @@ pub struct RamData {
     pub free_swap: u64,
     pub used_swap: u64,
     pub available_memory: u64,
+    pub meminfo: MemInfo,
 }
 
 impl RamData {
@@ -205,7 +338,8 @@ impl RamData {
             total_swap: sys.total_swap(),
             free_swap: sys.free_swap(),
             used_swap: sys.used_swap(),
-            available_memory: sys.available_memory()
+            available_memory: sys.available_memory(),
+            meminfo: MemInfo::new()
         };
             data
     }
@@ -227,7 +361,108 @@ impl RamData {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The detailed memory counters of `/proc/meminfo`, in bytes. Stays zeroed
+/// where the file doesn't exist.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemInfo {
+    pub mem_total: u64,
+    pub mem_free: u64,
+    pub mem_available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shmem: u64,
+    pub slab_reclaimable: u64,
+    pub slab_unreclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub anon_pages: u64,
+    pub mapped: u64,
+    pub page_tables: u64,
+    pub huge_pages_total: u64,
+    pub huge_pages_free: u64,
+    pub huge_page_size: u64,
+    pub committed_as: u64,
+    pub commit_limit: u64,
+}
+
+impl MemInfo {
+
+    pub fn new() -> MemInfo {
+        MemInfo::parse(&std::fs::read_to_string("/proc/meminfo").unwrap_or_default())
+    }
+
+    /// Parses `Key:   123 kB` lines; `HugePages_*` are page counts.
+    pub fn parse(text: &str) -> MemInfo {
+        let mut info = MemInfo::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let mut fields = value.split_whitespace();
+            let number = fields.next().and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+            let value = if fields.next() == Some("kB") { number * 1024 } else { number };
+            match key {
+                "MemTotal" => info.mem_total = value,
+                "MemFree" => info.mem_free = value,
+                "MemAvailable" => info.mem_available = value,
+                "Buffers" => info.buffers = value,
+                "Cached" => info.cached = value,
+                "Shmem" => info.shmem = value,
+                "SReclaimable" => info.slab_reclaimable = value,
+                "SUnreclaim" => info.slab_unreclaimable = value,
+                "Dirty" => info.dirty = value,
+                "Writeback" => info.writeback = value,
+                "AnonPages" => info.anon_pages = value,
+                "Mapped" => info.mapped = value,
+                "PageTables" => info.page_tables = value,
+                "HugePages_Total" => info.huge_pages_total = value,
+                "HugePages_Free" => info.huge_pages_free = value,
+                "Hugepagesize" => info.huge_page_size = value,
+                "Committed_AS" => info.committed_as = value,
+                "CommitLimit" => info.commit_limit = value,
+                _ => {}
+            }
+        }
+        info
+    }
+
+    /// Page cache that can be dropped: `Cached` without the shared memory
+    /// (tmpfs, shm segments) it includes.
+    pub fn page_cache(&self) -> u64 {
+        self.cached.saturating_sub(self.shmem)
+    }
+
+    /// Memory the kernel can give back under pressure.
+    pub fn reclaimable(&self) -> u64 {
+        self.page_cache() + self.buffers + self.slab_reclaimable
+    }
+
+    /// Memory that is really taken: everything that is neither free nor
+    /// reclaimable. Shared memory counts as used.
+    pub fn used(&self) -> u64 {
+        self.mem_total.saturating_sub(self.mem_free + self.reclaimable())
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Meminfo data***");
+        println!("buffers:...................{} Mb", self.buffers / 1024000);
+        println!("cached:....................{} Mb", self.cached / 1024000);
+        println!("shmem:.....................{} Mb", self.shmem / 1024000);
+        println!("slab_reclaimable:..........{} Mb", self.slab_reclaimable / 1024000);
+        println!("slab_unreclaimable:........{} Mb", self.slab_unreclaimable / 1024000);
+        println!("dirty:.....................{} Mb", self.dirty / 1024000);
+        println!("writeback:.................{} Mb", self.writeback / 1024000);
+        println!("anon_pages:................{} Mb", self.anon_pages / 1024000);
+        println!("mapped:....................{} Mb", self.mapped / 1024000);
+        println!("page_tables:...............{} Mb", self.page_tables / 1024000);
+        println!("huge_pages:................{} / {}", self.huge_pages_free, self.huge_pages_total);
+        println!("committed_as:..............{} Mb", self.committed_as / 1024000);
+        println!("commit_limit:..............{} Mb", self.commit_limit / 1024000);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OperationSystem {
     pub os_type: String,
     pub name_os: Option<String>,
@@ -236,6 +471,10 @@ pub struct OperationSystem {
     pub distribution: String,
     pub host_name: Option<String>,
     pub cpu_arch: String,
+    /// Seconds since boot.
+    pub uptime: u64,
+    /// The 1, 5 and 15 minute load averages.
+    pub load_average: [f64; 3],
 }
 
 impl OperationSystem {
@@ -248,7 +487,12 @@ impl OperationSystem {
             os_version: System::os_version(),
             distribution: System::distribution_id(),
             host_name: System::host_name(),
-            cpu_arch: System::cpu_arch()
+            cpu_arch: System::cpu_arch(),
+            uptime: System::uptime(),
+            load_average: {
+                let load = System::load_average();
+                [load.one, load.five, load.fifteen]
+            }
         };
         os_data
     }
@@ -263,41 +507,29 @@ impl OperationSystem {
         println!("distribution............{}", self.distribution);
         println!("host_name...............{:?}", self.host_name);
         println!("cpu_arch................{}", self.cpu_arch);
+        println!("uptime..................{} s", self.uptime);
+        println!("load average............{:?}", self.load_average);
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AllNetworksData {
-    interface_name: String,
-    network_ip_networks:String,
-    network_mac_address: String,
-    total_errors_on_received: u64,
-    total_errors_on_transmitted: u64,
-    total_packets_received: u64,
-    total_packets_transmitted: u64,
-    mtu: u64,
-}
-impl AllNetworksData {
-    pub fn to_string(&self) -> String {
-        "interface name:              ".to_owned() + &(self.interface_name.to_string()) + 
-        "\n" + 
-        "network ip networks:         " + &(self.network_ip_networks) +
-        "\n" + 
-        "network mac address:         " + &(self.network_mac_address) +
-        "\n" + 
-        "total errors on received:    " + &(self.total_errors_on_received.to_string()) +
-        "\n" + 
-        "total errors on transmitted: " + &(self.total_errors_on_transmitted.to_string()) +
-        "\n" + 
-        "total packets received:      " + &(self.total_packets_received.to_string()) +
-        "\n" + 
-        "total packets transmitted:   " + &(self.total_packets_transmitted.to_string()) +
-        "\n" + 
-        "mtu:                         " + &(self.mtu.to_string())
-    }
+    pub interface_name: String,
+    pub network_ip_networks:String,
+    pub network_mac_address: String,
+    pub total_errors_on_received: u64,
+    pub total_errors_on_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub mtu: u64,
+    pub addresses: Vec<InterfaceAddress>,
+    pub link: LinkInfo,
 }
-
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NetworkData {
     pub data_network: Vec<AllNetworksData>,
     pub network_len: i32,
@@ -310,7 +542,10 @@ impl NetworkData {
         let mut len: i32 = 0;
         let mut datas: Vec<AllNetworksData> = Vec::new();
         for (interface_name, network) in &networks {
-            let ip_str: String = network.ip_networks().into_iter().map(|i| i.to_string()).collect::<String>();
+            let addresses: Vec<InterfaceAddress> = network.ip_networks().iter()
+                .map(|i| InterfaceAddress { address: i.addr, prefix: i.prefix })
+                .collect();
+            let ip_str: String = addresses.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
             let tmp = AllNetworksData {
                 interface_name: interface_name.to_string(),
                 network_ip_networks: ip_str,
@@ -319,11 +554,16 @@ impl NetworkData {
                 total_errors_on_transmitted: network.total_errors_on_transmitted(),
                 total_packets_received: network.total_packets_received(),
                 total_packets_transmitted: network.total_packets_transmitted(),
-                mtu: network.mtu()
+                total_received: network.total_received(),
+                total_transmitted: network.total_transmitted(),
+                mtu: network.mtu(),
+                addresses,
+                link: LinkInfo::read(interface_name)
             };
             datas.push(tmp);
             len += 1;
         }
+        datas.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
 
         NetworkData {
             data_network: datas,
@@ -344,6 +584,10 @@ impl NetworkData {
           println!("total_errors_on_received......{}", network.total_errors_on_received);
           println!("total_errors_on_transmitted...{}", network.total_errors_on_transmitted);
           println!("mtu...........................{}", network.mtu);
+          println!("kind..........................{}", network.link.kind);
+          println!("operstate.....................{}", network.link.operstate);
+          println!("speed.........................{:?}", network.link.speed);
+          println!("driver........................{:?}", network.link.driver);
       }
   }
 
@@ -354,12 +598,13 @@ impl NetworkData {
 
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ComponentData {
-    label: String,
-    temperature: i64,
-    max_temp: i64,
-    critical_temp: i64,
+    pub label: String,
+    pub temperature: i64,
+    pub max_temp: i64,
+    pub critical_temp: i64,
 }
 
 impl ComponentData {
@@ -402,7 +647,8 @@ impl ComponentData {
 }
 
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ComponentsData {
     pub component_data_v: Vec<ComponentData>
 }
@@ -442,7 +688,8 @@ impl ComponentsData {
 }
 
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AllData {
     pub cpu_data: CpuData,
     pub gpu_data: GpuData,
@@ -450,28 +697,23 @@ pub struct AllData {
     pub ram_data: RamData,
     pub os_data: OperationSystem,
     pub network_data: NetworkData,
-    pub components_data: ComponentsData
+    pub components_data: ComponentsData,
+    pub cgroup_data: CgroupData,
+    pub socket_data: SocketsData,
+    pub hwmon_data: HwmonData,
+    pub pressure_data: PressureData,
+    pub kernel_data: KernelData,
+    pub block_data: BlockData,
+    pub wireless_data: WirelessData,
+    pub routing_data: RoutingData,
+    pub sessions_data: SessionsData,
+    pub processes_data: ProcessesData,
+    /// Collectors whose part is left over from an earlier sample.
+    pub degraded: Vec<Degraded>
 }
 
 impl AllData {
 
-    pub fn new() -> AllData {
-        AllData {
-            cpu_data: CpuData::new(),
-            gpu_data: GpuData::new(),
-            dram_data: DramData::new(),
-            ram_data: RamData::new(),
-            os_data: OperationSystem::new(),
-            network_data: NetworkData::new(),
-            components_data: ComponentsData::new()
-        }
-    }
-
-    #[allow(dead_code)]
-    pub fn update_all_data(&mut self) {
-        *self = Self::new();
-    }
-
     #[allow(dead_code)]
     pub fn update_all_cpu(&mut self) {
         self.cpu_data.update_cpu_data();
@@ -508,3 +750,99 @@ impl AllData {
     }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:       16309340 kB
+MemFree:         1204532 kB
+MemAvailable:    9311208 kB
+Buffers:          412260 kB
+Cached:          7893104 kB
+SwapCached:         2012 kB
+Active:          6870180 kB
+Shmem:            611828 kB
+SReclaimable:     522936 kB
+SUnreclaim:       161412 kB
+Dirty:              1252 kB
+Writeback:             0 kB
+AnonPages:       5180464 kB
+Mapped:          1049460 kB
+PageTables:        61272 kB
+CommitLimit:    12348812 kB
+Committed_AS:   17452396 kB
+HugePages_Total:       4
+HugePages_Free:        3
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn parses_meminfo() {
+        let info = MemInfo::parse(MEMINFO);
+        assert_eq!(info.mem_total, 16309340 * 1024);
+        assert_eq!(info.mem_free, 1204532 * 1024);
+        assert_eq!(info.mem_available, 9311208 * 1024);
+        assert_eq!(info.buffers, 412260 * 1024);
+        assert_eq!(info.cached, 7893104 * 1024);
+        assert_eq!(info.shmem, 611828 * 1024);
+        assert_eq!(info.slab_reclaimable, 522936 * 1024);
+        assert_eq!(info.slab_unreclaimable, 161412 * 1024);
+        assert_eq!(info.dirty, 1252 * 1024);
+        assert_eq!(info.anon_pages, 5180464 * 1024);
+        assert_eq!(info.mapped, 1049460 * 1024);
+        assert_eq!(info.page_tables, 61272 * 1024);
+        assert_eq!(info.commit_limit, 12348812 * 1024);
+        assert_eq!(info.committed_as, 17452396 * 1024);
+        // page counts, not kB
+        assert_eq!(info.huge_pages_total, 4);
+        assert_eq!(info.huge_pages_free, 3);
+        assert_eq!(info.huge_page_size, 2048 * 1024);
+
+        assert_eq!(info.page_cache(), (7893104 - 611828) * 1024);
+        assert_eq!(info.reclaimable(), (7893104 - 611828 + 412260 + 522936) * 1024);
+        assert_eq!(info.used(), (16309340 - 1204532 - (7893104 - 611828 + 412260 + 522936)) * 1024);
+    }
+
+    #[test]
+    fn parses_empty_meminfo() {
+        assert_eq!(MemInfo::parse(""), MemInfo::default());
+    }
+
+    const STAT: &str = "cpu  10132153 290696 3084719 46828483 16683 0 25195 0 175628 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 23933 0
+cpu1 1335891 35111 467981 13384863 5239 0 2965 1 19842 0
+intr 199292346 34 9 0 0 0 0 3 0 1 0 0 0 118 0 0 0
+ctxt 444318590
+btime 1700000000
+";
+
+    #[test]
+    fn parses_proc_stat() {
+        let (total, cores) = CpuTimes::parse_stat(STAT);
+        assert_eq!(total, CpuTimes {
+            user: 10132153,
+            nice: 290696,
+            system: 3084719,
+            idle: 46828483,
+            iowait: 16683,
+            irq: 0,
+            softirq: 25195,
+            steal: 0,
+        });
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores[1].values(), [1335891, 35111, 467981, 13384863, 5239, 0, 2965, 1]);
+    }
+
+    #[test]
+    fn computes_shares_since_a_sample() {
+        let before = CpuTimes { user: 100, system: 50, idle: 800, iowait: 50, ..CpuTimes::default() };
+        let now = CpuTimes { user: 130, system: 60, idle: 850, iowait: 60, ..CpuTimes::default() };
+        let shares = now.shares_since(&before).unwrap();
+        assert_eq!(shares[0], 30.0);
+        assert_eq!(shares[2], 10.0);
+        assert_eq!(shares[CpuTimes::IDLE], 50.0);
+        assert_eq!(shares[CpuTimes::IOWAIT], 10.0);
+        assert_eq!(now.shares_since(&now), None);
+    }
+}
@@ -0,0 +1,202 @@
+use super::record::Sample;
+
+pub const CPU_WARNING: i32 = 90;
+pub const RAM_WARNING: f32 = 90.0;
+pub const DISK_WARNING: f32 = 90.0;
+pub const DISK_CRITICAL: f32 = 97.0;
+/// How close to its critical temperature a sensor may get before it is reported.
+pub const TEMP_MARGIN: i64 = 10;
+/// PSI `avg10` shares (in %) of stalled time.
+pub const PSI_WARNING: f64 = 10.0;
+pub const PSI_CRITICAL: f64 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+/// What an alert is about.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertKind {
+    Cpu,
+    Ram,
+    Disk,
+    Raid,
+    Pressure,
+    /// A collector that is late or failed, by its watchdog name.
+    Stale(String),
+    Temperature,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub severity: Severity,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+/// The headline numbers of one host, computed from its two latest samples.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub cpu_usage: i32,
+    pub ram_percent: f32,
+    pub swap_percent: f32,
+    pub worst_disk: Option<(String, f32)>,
+    pub hottest: Option<(String, i64)>,
+    pub rx_rate: Option<f64>,
+    pub tx_rate: Option<f64>,
+    pub alerts: Vec<Alert>,
+}
+
+impl Summary {
+
+    pub fn new(previous: Option<&Sample>, current: &Sample) -> Summary {
+        let data = &current.data;
+        let worst_disk = data.dram_data.disks.iter()
+            .filter(|(_, d)| d.total_space > 0)
+            .map(|(name, d)| (name.clone(), d.used_percent()))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let hottest = data.components_data.component_data_v.iter()
+            .filter(|c| !c.label.is_empty())
+            .max_by_key(|c| c.temperature)
+            .map(|c| (c.label.clone(), c.temperature));
+
+        let (mut rx_rate, mut tx_rate) = (None, None);
+        if let Some(prev) = previous {
+            let secs = current.timestamp_ms.saturating_sub(prev.timestamp_ms) as f64 / 1000.0;
+            if secs > 0.0 {
+                let (rx, tx) = network_totals(current);
+                let (prev_rx, prev_tx) = network_totals(prev);
+                rx_rate = Some(rx.saturating_sub(prev_rx) as f64 / secs);
+                tx_rate = Some(tx.saturating_sub(prev_tx) as f64 / secs);
+            }
+        }
+
+        let cpu_usage = previous
+            .and_then(|prev| data.cpu_data.usage_since(&prev.data.cpu_data))
+            .map(|usage| usage.round() as i32)
+            .unwrap_or(data.cpu_data.global_cpu_usage);
+
+        let mut summary = Summary {
+            cpu_usage,
+            ram_percent: percent(data.ram_data.used_memory, data.ram_data.total_memory),
+            swap_percent: percent(data.ram_data.used_swap, data.ram_data.total_swap),
+            worst_disk,
+            hottest,
+            rx_rate,
+            tx_rate,
+            alerts: Vec::new(),
+        };
+        summary.alerts = summary.check(current);
+        summary
+    }
+
+    pub fn severity(&self) -> Option<Severity> {
+        self.alerts.iter().map(|a| a.severity).max()
+    }
+
+    /// The first of the most severe alerts.
+    pub fn worst_alert(&self) -> Option<&Alert> {
+        self.alerts.iter().rev().max_by_key(|a| a.severity)
+    }
+
+    fn check(&self, current: &Sample) -> Vec<Alert> {
+        let mut alerts: Vec<Alert> = Vec::new();
+        if self.cpu_usage >= CPU_WARNING {
+            alerts.push(alert(Severity::Warning, AlertKind::Cpu, format!("CPU usage {}%", self.cpu_usage)));
+        }
+        if self.ram_percent >= RAM_WARNING {
+            alerts.push(alert(Severity::Warning, AlertKind::Ram, format!("RAM usage {:.0}%", self.ram_percent)));
+        }
+        for (name, disk) in &current.data.dram_data.disks {
+            let full = disk.used_percent();
+            if let Some(severity) = disk_severity(full) {
+                alerts.push(alert(severity, AlertKind::Disk, format!("{} is {:.0}% full", name, full)));
+            }
+            if let Some(inodes) = disk.inode_percent() {
+                if let Some(severity) = disk_severity(inodes) {
+                    alerts.push(alert(severity, AlertKind::Disk, format!("{} has used {:.0}% of its inodes", name, inodes)));
+                }
+            }
+        }
+        for array in current.data.block_data.degraded_arrays() {
+            alerts.push(alert(Severity::Critical, AlertKind::Raid, format!("{} ({}) is degraded [{}]", array.name, array.level, array.status)));
+        }
+        let pressure = &current.data.pressure_data;
+        for (resource, psi) in [("CPU", &pressure.cpu), ("memory", &pressure.memory), ("I/O", &pressure.io)] {
+            if let Some(severity) = psi.as_ref().and_then(|p| psi_severity(p.some.avg10)) {
+                alerts.push(alert(severity, AlertKind::Pressure, format!("{} pressure {:.0}%", resource, psi.as_ref().map(|p| p.some.avg10).unwrap_or(0.0))));
+            }
+        }
+        for degraded in &current.data.degraded {
+            alerts.push(alert(Severity::Warning, AlertKind::Stale(degraded.collector.clone()), format!("{} data is stale: {}", degraded.collector, degraded.error)));
+        }
+        for cmp in &current.data.components_data.component_data_v {
+            if cmp.critical_temp > 0 && cmp.temperature >= cmp.critical_temp {
+                alerts.push(alert(Severity::Critical, AlertKind::Temperature, format!("{} at {}°C", cmp.label, cmp.temperature)));
+            } else if cmp.critical_temp > 0 && cmp.temperature >= cmp.critical_temp - TEMP_MARGIN {
+                alerts.push(alert(Severity::Warning, AlertKind::Temperature, format!("{} at {}°C", cmp.label, cmp.temperature)));
+            }
+        }
+        alerts
+    }
+}
+
+pub fn percent(part: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 * 100.0 / total as f32
+    }
+}
+
+/// Applies to both the byte and the inode usage of a filesystem.
+pub fn disk_severity(full: f32) -> Option<Severity> {
+    if full >= DISK_CRITICAL {
+        Some(Severity::Critical)
+    } else if full >= DISK_WARNING {
+        Some(Severity::Warning)
+    } else {
+        None
+    }
+}
+
+pub fn psi_severity(avg: f64) -> Option<Severity> {
+    if avg >= PSI_CRITICAL {
+        Some(Severity::Critical)
+    } else if avg >= PSI_WARNING {
+        Some(Severity::Warning)
+    } else {
+        None
+    }
+}
+
+fn alert(severity: Severity, kind: AlertKind, message: String) -> Alert {
+    Alert { severity, kind, message }
+}
+
+fn network_totals(sample: &Sample) -> (u64, u64) {
+    sample.data.network_data.data_network.iter()
+        .filter(|n| n.interface_name != "lo")
+        .fold((0, 0), |(rx, tx), n| (rx + n.total_received, tx + n.total_transmitted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worst_alert_is_the_first_most_severe() {
+        let summary = Summary {
+            alerts: vec![
+                alert(Severity::Warning, AlertKind::Cpu, "CPU usage 95%".to_owned()),
+                alert(Severity::Critical, AlertKind::Raid, "md0 (raid1) is degraded [U_]".to_owned()),
+                alert(Severity::Critical, AlertKind::Disk, "/ is 98% full".to_owned()),
+            ],
+            ..Summary::default()
+        };
+        assert_eq!(summary.worst_alert().map(|a| &a.kind), Some(&AlertKind::Raid));
+        assert_eq!(Summary::default().worst_alert(), None);
+    }
+}
@@ -1,24 +1,124 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::{fmt, io, mem};
+
 use structs::AllData;
+use cgroup::CgroupNode;
+use process::{ProcessDetail, ProcessInfo};
+use chart::{LineChart, Series};
+use host::Host;
+use record::{Recorder, Replay, Sample, REPLAY_SPEEDS};
+use remote::{RemoteEvent, RemoteStatus};
+use summary::{disk_severity, psi_severity, AlertKind, Severity};
+use options::Options;
+use query::Query;
+use watchdog::Watchdog;
 
 use iced::widget::pane_grid::{self, PaneGrid};
-use iced::widget::{button, column, container, rich_text, scrollable, span, text};
-use iced::{color, font, Center, Element, Fill, Font};
+use iced::widget::{button, column, container, mouse_area, pick_list, rich_text, row, scrollable, slider, span, text, text_input};
+use iced::time::{self, Duration, Instant};
+use iced::{color, font, window, Center, Element, Fill, FillPortion, Font, Subscription, Task};
 
-use super::structs;
+use super::{blockdev, cgroup, chart, host, kernel, options, pressure, process, query, record, remote, routes, sessions, sockets, structs, summary, watchdog, wireless};
 
+const REPLAY_TICK: Duration = Duration::from_millis(100);
+const ALL_STATES: &str = "ALL";
+/// How many views a pane remembers for its back button.
+const VIEW_HISTORY: usize = 50;
+const TITLE: &str = "Monytoring System";
+const MINI_SIZE: iced::Size = iced::Size::new(260.0, 330.0);
+const MINI_COLORS: [iced::Color; 4] = [color!(0x4070d0), color!(0x40a040), color!(0xe0a030), color!(0xd04040)];
 
 pub struct ViewContainer {
     panes: pane_grid::State<Pane>,
-    count: i32
+    count: i32,
+    hosts: Vec<Host>,
+    interval: Duration,
+    /// Written by the collector worker; `None` when not recording or once
+    /// writing failed.
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    replay: Option<Replay>,
+    saved_queries: Vec<String>,
+    /// Whether a sample of the local host is being taken.
+    collecting: bool,
+    watchdog: Arc<Mutex<Watchdog>>,
+    main_window: Option<window::Id>,
+    mini_window: Option<window::Id>,
+    window_settings: window::Settings,
+    detached: BTreeMap<window::Id, Detached>,
+    /// The detached window whose panes are in `panes` while one of its
+    /// messages is handled.
+    swapped_in: Option<window::Id>,
+    /// What went wrong in the background, by what was being done; shown
+    /// above the panes until doing it again succeeds.
+    failures: BTreeMap<&'static str, String>,
+    /// Numbers the saved query lists, so a write of an older list never
+    /// replaces a newer one; holds the number of the list last written.
+    queries_written: Arc<Mutex<u64>>,
+    queries_version: u64,
+}
+
+/// What the collector worker hands back.
+#[derive(Debug, Clone)]
+pub struct Collected {
+    sample: Box<Sample>,
+    /// Why appending to the recording failed; it has stopped then.
+    recording_error: Option<String>,
+}
+
+/// A window torn off the main one, with a pane grid of its own. It shows
+/// the same hosts, so it is refreshed by the same loop.
+struct Detached {
+    panes: pane_grid::State<Pane>,
+    count: i32,
+    /// Set once closing was asked for: the window then offers to dock its
+    /// panes back into the main window.
+    closing: bool,
+    /// The main pane the window was torn from, or the one that took its
+    /// place; its panes dock beside it. `None` when it was torn from another
+    /// detached window, whose pane ids mean nothing in the main grid.
+    origin: Option<pane_grid::Pane>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Data(pane_grid::Pane, TypeData),
+    Show(pane_grid::Pane, TypeData),
+    Back(pane_grid::Pane),
+    Forward(pane_grid::Pane),
+    Detach(pane_grid::Pane),
+    /// A message of the panes of a detached window.
+    Window(window::Id, Box<Message>),
+    CloseRequested(window::Id),
+    WindowClosed(window::Id),
+    Dock(window::Id),
+    CloseWindow(window::Id),
+    KeepWindow(window::Id),
+    ToggleMini,
+    OpenMainWindow,
+    DragMini,
     Resized(pane_grid::ResizeEvent),
+    SelectHost(pane_grid::Pane, usize),
+    OpenHost(pane_grid::Pane, usize),
+    CgroupSort(pane_grid::Pane, CgroupSort),
+    CgroupToggle(pane_grid::Pane, String),
+    SocketState(pane_grid::Pane, String),
+    SocketPort(pane_grid::Pane, String),
+    ProcessToggle(pane_grid::Pane, u32),
+    ProcessSearch(pane_grid::Pane, String),
+    InspectProcess(pane_grid::Pane, u32),
+    SaveQuery(pane_grid::Pane),
+    DeleteQuery(usize),
+    Tick(Instant),
+    Collected(Result<Collected, String>),
+    QueriesStored(Result<(), String>),
+    Remote(RemoteEvent),
+    ReplayToggle,
+    ReplaySeek(u32),
+    ReplaySpeed(f32),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum TypeData {
     Cpu,
     Gpu,
@@ -26,40 +126,617 @@ pub enum TypeData {
     Ram,
     Os,
     Network,
+    Hosts,
+    Cgroups,
+    Connections,
+    Sensors,
+    Pressure,
+    Kernel,
+    Blocks,
+    Routes,
+    Sessions,
+    Processes,
+    ProcessDetail,
+    #[default]
     Empty,
 }
 
+impl TypeData {
+    /// The views a pane can switch to. The process inspector needs a pid
+    /// and is opened from the process tree instead.
+    pub const ALL: [TypeData; 17] = [
+        TypeData::Empty,
+        TypeData::Cpu,
+        TypeData::Gpu,
+        TypeData::Dram,
+        TypeData::Blocks,
+        TypeData::Ram,
+        TypeData::Os,
+        TypeData::Sessions,
+        TypeData::Network,
+        TypeData::Routes,
+        TypeData::Processes,
+        TypeData::Cgroups,
+        TypeData::Connections,
+        TypeData::Sensors,
+        TypeData::Pressure,
+        TypeData::Kernel,
+        TypeData::Hosts,
+    ];
+}
+
+impl TypeData {
+    /// The collectors whose data the view shows.
+    fn collectors(&self) -> &'static [&'static str] {
+        match self {
+            TypeData::Cpu => &["cpu"],
+            TypeData::Gpu => &["gpu"],
+            TypeData::Dram => &["disk"],
+            TypeData::Ram => &["ram"],
+            TypeData::Os => &["os"],
+            TypeData::Network => &["network", "wireless"],
+            TypeData::Cgroups => &["cgroups"],
+            TypeData::Connections => &["sockets"],
+            TypeData::Sensors => &["components", "hwmon"],
+            TypeData::Pressure => &["pressure"],
+            TypeData::Kernel => &["kernel"],
+            TypeData::Blocks => &["blocks"],
+            TypeData::Routes => &["routes"],
+            TypeData::Sessions => &["sessions"],
+            TypeData::Processes | TypeData::ProcessDetail => &["processes"],
+            TypeData::Hosts | TypeData::Empty => &[],
+        }
+    }
+
+    /// The view showing what an alert is about.
+    fn for_alert(kind: &AlertKind) -> Option<TypeData> {
+        match kind {
+            AlertKind::Cpu => Some(TypeData::Cpu),
+            AlertKind::Ram => Some(TypeData::Ram),
+            AlertKind::Disk => Some(TypeData::Dram),
+            AlertKind::Raid => Some(TypeData::Blocks),
+            AlertKind::Pressure => Some(TypeData::Pressure),
+            AlertKind::Temperature => Some(TypeData::Sensors),
+            AlertKind::Stale(collector) => TypeData::ALL.into_iter().find(|t| t.collectors().contains(&collector.as_str())),
+        }
+    }
+}
+
+impl fmt::Display for TypeData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TypeData::Cpu => "CPU",
+            TypeData::Gpu => "GPU",
+            TypeData::Dram => "DRAM",
+            TypeData::Ram => "RAM",
+            TypeData::Os => "OS",
+            TypeData::Network => "NETWORK",
+            TypeData::Hosts => "HOSTS",
+            TypeData::Cgroups => "CGROUPS",
+            TypeData::Connections => "CONNECTIONS",
+            TypeData::Sensors => "SENSORS",
+            TypeData::Pressure => "PRESSURE",
+            TypeData::Kernel => "KERNEL",
+            TypeData::Blocks => "BLOCK DEVICES",
+            TypeData::Routes => "ROUTES & DNS",
+            TypeData::Sessions => "SESSIONS",
+            TypeData::Processes => "PROCESSES",
+            TypeData::ProcessDetail => "PROCESS",
+            TypeData::Empty => "OVERVIEW",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CgroupSort {
+    #[default]
+    Name,
+    Cpu,
+    Memory,
+    Io,
+    Pids,
+}
+
 impl ViewContainer {
     fn new(prev_count: i32, _data_pype: TypeData) -> Self {
-        let state_pane = pane_grid::State::new(Pane::new(_data_pype));
+        let state_pane = pane_grid::State::new(Pane::new(_data_pype, 0));
         let rez = ViewContainer {
             panes: state_pane.0,
-            count: prev_count + 1
+            count: prev_count + 1,
+            hosts: vec![Host::local("local")],
+            interval: Options::default().interval,
+            recorder: Arc::new(Mutex::new(None)),
+            replay: None,
+            saved_queries: Vec::new(),
+            collecting: false,
+            watchdog: Arc::new(Mutex::new(Watchdog::new())),
+            main_window: None,
+            mini_window: None,
+            window_settings: window::Settings::default(),
+            detached: BTreeMap::new(),
+            swapped_in: None,
+            failures: BTreeMap::new(),
+            queries_written: Arc::new(Mutex::new(0)),
+            queries_version: 0,
         };
         return rez;
     }
 
-    pub fn update(&mut self, message: Message) {
+    /// Builds the container for the given command line options: opens the
+    /// recording file for `--record` or loads the session for `--replay`,
+    /// and adds a host for every agent to connect to.
+    pub fn from_options(options: &Options) -> io::Result<Self> {
+        let mut rez = ViewContainer::new(0, TypeData::Empty);
+        rez.interval = options.interval;
+        rez.saved_queries = query::load_saved_queries();
+        rez.hosts.extend(options.connect.iter().map(|address| Host::remote(address)));
+        if let Some(path) = &options.replay {
+            let replay = Replay::open(path)?;
+            rez.hosts[0] = Host::local("replay");
+            rez.hosts[0].push(replay.current().clone());
+            rez.replay = Some(replay);
+        } else {
+            if let Some(path) = &options.record {
+                rez.recorder = Arc::new(Mutex::new(Some(Recorder::new(path)?)));
+            }
+        }
+        Ok(rez)
+    }
+
+    /// Samples the local host on a blocking thread of the runtime, so slow
+    /// collectors don't freeze the windows. A tick arriving while the last
+    /// sample is still being taken is skipped; the watchdog keeps that from
+    /// taking longer than its timeout.
+    fn collect(&mut self) -> Task<Message> {
+        if self.replay.is_some() || self.collecting {
+            return Task::none();
+        }
+        self.collecting = true;
+        let watchdog = Arc::clone(&self.watchdog);
+        let recorder = Arc::clone(&self.recorder);
+        let pids = self.inspected_pids();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let mut sample = Sample::new(watchdog.lock().unwrap().collect());
+                    let mut recorder = recorder.lock().unwrap();
+                    let recording_error = match recorder.as_mut().map(|r| r.append(&sample)) {
+                        Some(Err(e)) => {
+                            *recorder = None;
+                            Some(e.to_string())
+                        }
+                        _ => None,
+                    };
+                    sample.process_details = pids.into_iter().map(|pid| (pid, ProcessDetail::read(pid))).collect();
+                    Collected { sample: Box::new(sample), recording_error }
+                }).await
+                    .map_err(|e| e.to_string())
+            },
+            Message::Collected,
+        )
+    }
+
+    /// The processes inspected in panes of the local host, in every window.
+    fn inspected_pids(&self) -> HashSet<u32> {
+        let detached = self.detached.values().flat_map(|window| window.panes.iter());
+        self.panes.iter().chain(detached)
+            .filter(|(_, pane)| pane.host == 0)
+            .filter_map(|(_, pane)| pane.inspected_pid)
+            .collect()
+    }
+
+    fn push_local(&mut self, mut sample: Sample) {
+        let details = mem::take(&mut sample.process_details);
+        self.hosts[0].push(sample);
+        let detached = self.detached.values_mut().flat_map(|window| window.panes.iter_mut());
+        for (_, pane) in self.panes.iter_mut().chain(detached) {
+            pane.update_process_detail(&details);
+        }
+    }
+
+    /// Splits `pane` to show `new_pane`; the other panes stay as they are.
+    fn open(&mut self, pane: pane_grid::Pane, new_pane: Pane) {
+        if self.panes.split(pane_grid::Axis::Vertical, pane, new_pane).is_some() {
+            self.count += 1;
+        }
+    }
+
+    /// Adds the panes of a detached window next to the pane it was torn
+    /// from.
+    fn dock(&mut self, window: Detached) {
+        let mut panes: Vec<(&pane_grid::Pane, &Pane)> = window.panes.iter().collect();
+        panes.sort_by_key(|(id, _)| **id);
+        for (_, pane) in panes {
+            // the origin may have been closed since; then the oldest pane
+            let beside = match window.origin {
+                Some(origin) if self.panes.get(origin).is_some() => origin,
+                _ => *self.panes.iter().map(|(id, _)| id).min().unwrap(),
+            };
+            self.open(beside, pane.clone());
+        }
+    }
+
+    /// Opens the main window, or only the mini monitor for `--mini`, and
+    /// takes the first sample.
+    pub fn start(&mut self, settings: window::Settings, mini: bool) -> Task<Message> {
+        self.window_settings = settings;
+        let open = if mini { self.toggle_mini() } else { self.open_main_window() };
+        Task::batch([open, self.collect()])
+    }
+
+    fn open_main_window(&mut self) -> Task<Message> {
+        if let Some(id) = self.main_window {
+            return window::gain_focus(id);
+        }
+        let (id, open) = window::open(self.window_settings.clone());
+        self.main_window = Some(id);
+        open.discard()
+    }
+
+    /// Opens the mini monitor, a small borderless window staying on top of
+    /// the others, or closes it.
+    fn toggle_mini(&mut self) -> Task<Message> {
+        if let Some(id) = self.mini_window {
+            return window::close(id);
+        }
+        let (id, open) = window::open(window::Settings {
+            size: MINI_SIZE,
+            resizable: false,
+            decorations: false,
+            level: window::Level::AlwaysOnTop,
+            exit_on_close_request: false,
+            ..self.window_settings.clone()
+        });
+        self.mini_window = Some(id);
+        open.discard()
+    }
+
+    /// Takes `pane` out of the grid, leaving an empty pane if it was the
+    /// last, and tells which pane now has its place.
+    fn take_pane(&mut self, pane: pane_grid::Pane) -> Option<(Pane, pane_grid::Pane)> {
+        if self.panes.len() > 1 {
+            let (taken, sibling) = self.panes.close(pane)?;
+            self.count = (self.count - 1).max(1);
+            Some((taken, sibling))
+        } else {
+            let p = self.panes.get_mut(pane)?;
+            let host = p.host;
+            Some((mem::replace(p, Pane::new(TypeData::Empty, host)), pane))
+        }
+    }
+
+    /// Puts the panes of a detached window in place of the main ones, or
+    /// back, so pane messages of that window are handled like any other.
+    /// The window keeps the main panes meanwhile, so they are still seen by
+    /// whatever goes over the panes of every window.
+    fn swap_panes(&mut self, id: window::Id) {
+        if let Some(window) = self.detached.get_mut(&id) {
+            mem::swap(&mut self.panes, &mut window.panes);
+            mem::swap(&mut self.count, &mut window.count);
+        }
+    }
+
+    /// Writes the saved queries on a blocking thread, like `collect`. The
+    /// writes take turns, and one that comes after a newer list was written
+    /// is dropped.
+    fn store_queries(&mut self) -> Task<Message> {
+        self.queries_version += 1;
+        let (queries, version) = (self.saved_queries.clone(), self.queries_version);
+        let written = Arc::clone(&self.queries_written);
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let mut written = written.lock().unwrap();
+                    if *written > version {
+                        return Ok(());
+                    }
+                    *written = version;
+                    query::store_saved_queries(&queries).map_err(|e| e.to_string())
+                }).await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result)
+            },
+            Message::QueriesStored,
+        )
+    }
+
+    fn show_replay_position(&mut self) {
+        if let Some(replay) = &self.replay {
+            let samples = replay.previous().into_iter().chain(std::iter::once(replay.current()));
+            self.hosts[0].reset(samples.cloned());
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::Data(pane, data_type) => {
-                if self.count >= 2 {
-                    self.panes.close(*self.panes.iter().last().unwrap().0);
-                    self.panes.split(pane_grid::Axis::Vertical, pane, Pane::new(data_type));
-                } else {
-                    self.panes.split(pane_grid::Axis::Vertical, pane, Pane::new( data_type));
-                    self.count += 1;
+                let host = self.panes.get(pane).map(|p| p.host).unwrap_or(0);
+                self.open(pane, Pane::new(data_type, host));
+            }
+            Message::Show(pane, data_type) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.show(data_type);
+                }
+            }
+            Message::Back(pane) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.back();
+                }
+            }
+            Message::Forward(pane) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.forward();
+                }
+            }
+            Message::Detach(pane) => {
+                let Some((taken, sibling)) = self.take_pane(pane) else { return Task::none() };
+                let origin = if self.swapped_in.is_none() { Some(sibling) } else { None };
+                let (id, open) = window::open(window::Settings {
+                    exit_on_close_request: false,
+                    ..self.window_settings.clone()
+                });
+                self.detached.insert(id, Detached { panes: pane_grid::State::new(taken).0, count: 1, closing: false, origin });
+                return open.discard();
+            }
+            Message::Window(id, message) => {
+                if !self.detached.contains_key(&id) {
+                    return Task::none();
+                }
+                self.swap_panes(id);
+                self.swapped_in = Some(id);
+                let task = self.update(*message);
+                self.swapped_in = None;
+                self.swap_panes(id);
+                return task;
+            }
+            Message::CloseRequested(id) => {
+                match self.detached.get_mut(&id) {
+                    Some(window) => window.closing = true,
+                    None => return window::close(id),
+                }
+            }
+            // The application runs as long as the main window or the mini
+            // monitor is open; detached windows go with the main window.
+            Message::WindowClosed(id) => {
+                if self.main_window == Some(id) {
+                    self.main_window = None;
+                    if self.mini_window.is_none() {
+                        return iced::exit();
+                    }
+                    return Task::batch(self.detached.keys().map(|id| window::close(*id)));
+                }
+                if self.mini_window == Some(id) {
+                    self.mini_window = None;
+                    if self.main_window.is_none() {
+                        return iced::exit();
+                    }
+                }
+                self.detached.remove(&id);
+            }
+            Message::ToggleMini => {
+                return self.toggle_mini();
+            }
+            Message::OpenMainWindow => {
+                return self.open_main_window();
+            }
+            Message::DragMini => {
+                if let Some(id) = self.mini_window {
+                    return window::drag(id);
+                }
+            }
+            Message::Dock(id) => {
+                if let Some(window) = self.detached.remove(&id) {
+                    self.dock(window);
+                }
+                return window::close(id);
+            }
+            Message::CloseWindow(id) => {
+                return window::close(id);
+            }
+            Message::KeepWindow(id) => {
+                if let Some(window) = self.detached.get_mut(&id) {
+                    window.closing = false;
                 }
             }
             Message::Resized(pane_grid::ResizeEvent { split, ratio }) => {
                 self.panes.resize(split, ratio);
             }
+            Message::SelectHost(pane, host) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.host = host;
+                }
+            }
+            Message::OpenHost(pane, host) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.host = host;
+                    p.show(TypeData::Empty);
+                }
+            }
+            Message::CgroupSort(pane, sort) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.cgroup_sort = sort;
+                }
+            }
+            Message::CgroupToggle(pane, path) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    if !p.cgroup_expanded.remove(&path) {
+                        p.cgroup_expanded.insert(path);
+                    }
+                }
+            }
+            Message::SocketState(pane, state) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.socket_state = if state == ALL_STATES { None } else { Some(state) };
+                }
+            }
+            Message::SocketPort(pane, port) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.socket_port = port;
+                }
+            }
+            Message::ProcessToggle(pane, pid) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    if !p.process_expanded.remove(&pid) {
+                        p.process_expanded.insert(pid);
+                    }
+                }
+            }
+            Message::ProcessSearch(pane, search) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.process_search = search;
+                }
+            }
+            Message::SaveQuery(pane) => {
+                let Some(query) = self.panes.get(pane).map(|p| p.process_search.trim().to_owned()) else { return Task::none() };
+                if !query.is_empty() && !self.saved_queries.contains(&query) {
+                    self.saved_queries.push(query);
+                    return self.store_queries();
+                }
+            }
+            Message::DeleteQuery(index) => {
+                if index < self.saved_queries.len() {
+                    self.saved_queries.remove(index);
+                    return self.store_queries();
+                }
+            }
+            Message::InspectProcess(pane, pid) => {
+                if let Some(p) = self.panes.get_mut(pane) {
+                    p.inspected_pid = Some(pid);
+                    p.process_detail = None;
+                    p.show(TypeData::ProcessDetail);
+                }
+                return self.collect();
+            }
+            Message::Tick(now) => {
+                match &mut self.replay {
+                    Some(replay) => {
+                        if replay.advance(now) {
+                            self.hosts[0].push(replay.current().clone());
+                        }
+                    }
+                    None => return self.collect(),
+                }
+            }
+            Message::Collected(result) => {
+                self.collecting = false;
+                match result {
+                    Ok(collected) => {
+                        self.failures.remove("collecting data");
+                        if let Some(e) = collected.recording_error {
+                            self.failures.insert("recording stopped", e);
+                        }
+                        self.push_local(*collected.sample);
+                    }
+                    Err(e) => {
+                        self.failures.insert("collecting data", e);
+                    }
+                }
+            }
+            Message::QueriesStored(result) => {
+                match result {
+                    Ok(()) => self.failures.remove("saving queries"),
+                    Err(e) => self.failures.insert("saving queries", e),
+                };
+            }
+            Message::ReplayToggle => {
+                if let Some(replay) = &mut self.replay {
+                    let position = replay.position();
+                    replay.toggle_play();
+                    if replay.position() != position {
+                        self.show_replay_position();
+                    }
+                }
+            }
+            Message::ReplaySeek(position) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.seek(position as usize);
+                }
+                self.show_replay_position();
+            }
+            Message::ReplaySpeed(speed) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.set_speed(speed);
+                }
+            }
+            Message::Remote(RemoteEvent { address, status }) => {
+                if let Some(host) = self.hosts.iter_mut().find(|h| h.address.as_ref() == Some(&address)) {
+                    match status {
+                        RemoteStatus::Connected(name) => {
+                            host.name = name;
+                            host.connected = true;
+                            host.error = None;
+                        }
+                        RemoteStatus::Sample(sample) => host.push(*sample),
+                        RemoteStatus::Disconnected(e) => {
+                            host.connected = false;
+                            host.error = Some(e);
+                        }
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let local = match &self.replay {
+            Some(replay) if replay.is_playing() => time::every(REPLAY_TICK).map(Message::Tick),
+            Some(_) => Subscription::none(),
+            None => time::every(self.interval).map(Message::Tick),
+        };
+        let remotes = self.hosts.iter()
+            .filter_map(|h| h.address.clone())
+            .map(|address| remote::connect(address).map(Message::Remote));
+        let windows = [
+            window::close_requests().map(Message::CloseRequested),
+            window::close_events().map(Message::WindowClosed),
+        ];
+        Subscription::batch(std::iter::once(local).chain(remotes).chain(windows))
+    }
+
+    pub fn title(&self, id: window::Id) -> String {
+        match self.detached.get(&id).and_then(|window| window.panes.iter().next()) {
+            Some((_, pane)) => format!("{} - {}", TITLE, pane.type_data),
+            None => TITLE.to_owned(),
         }
     }
 
-    pub fn view(&self) -> Element<Message> { 
-        let pane_grid = PaneGrid::new(&self.panes, |id: pane_grid::Pane, _pane: &Pane, _false_var: bool| {
+    pub fn view(&self, id: window::Id) -> Element<'_, Message> {
+        if self.mini_window == Some(id) {
+            return mini_view(&self.hosts[0], self.main_window.is_none());
+        }
+        if let Some(window) = self.detached.get(&id) {
+            let grid = self.pane_grid(&window.panes).map(move |message| Message::Window(id, Box::new(message)));
+            return if window.closing {
+                let prompt = row![
+                    text("Dock the panes of this window back into the main window?"),
+                    button("Dock").on_press(Message::Dock(id)).style(button::primary),
+                    button("Close").on_press(Message::CloseWindow(id)).style(button::danger),
+                    button("Cancel").on_press(Message::KeepWindow(id)).style(button::secondary),
+                ].spacing(10).align_y(Center);
+                container(column![prompt, grid].spacing(10)).padding(10).into()
+            } else {
+                container(grid).padding(10).into()
+            };
+        }
+        let pane_grid = self.pane_grid(&self.panes);
+        let mini = button(text(if self.mini_window.is_some() { "close mini monitor" } else { "mini monitor" }))
+            .on_press(Message::ToggleMini)
+            .style(button::secondary);
+        let toolbar = match &self.replay {
+            Some(replay) => row![replay_controls(replay), mini].spacing(10).align_y(Center),
+            None => row![mini],
+        };
+        let toolbar = self.failures.iter().fold(toolbar, |toolbar, (doing, error)| {
+            toolbar.push(text(format!("{}: {}", doing, error)).color(color!(0xd04040)))
+        }).spacing(10).align_y(Center);
+        container(column![toolbar, pane_grid].spacing(10)).padding(10).into()
+    }
+
+    fn pane_grid<'a>(&'a self, panes: &'a pane_grid::State<Pane>) -> Element<'a, Message> {
+        PaneGrid::new(panes, |id: pane_grid::Pane, _pane: &Pane, _false_var: bool| {
             pane_grid::Content::new({
-                view_content(id, _pane)
+                view_content(id, _pane, &self.hosts, &self.saved_queries, self.replay.is_some())
             }
         )
             .style(style::pane_style)
@@ -67,8 +744,8 @@ impl ViewContainer {
         .width(Fill)
         .height(Fill)
         .spacing(10)
-        .on_resize(10, Message::Resized);
-        container(pane_grid).padding(10).into()
+        .on_resize(10, Message::Resized)
+        .into()
     }
 }
 
@@ -78,136 +755,1319 @@ impl Default for ViewContainer {
     }
 }
 
-#[derive(Debug, Clone)]
+/// The mini monitor: CPU, RAM, network rates and the hottest sensor of the
+/// local host as small charts. The title row moves the borderless window.
+fn mini_view<'a>(host: &Host, standalone: bool) -> Element<'a, Message> {
+    let summary = host.summary().unwrap_or_default();
+    let summaries: Vec<summary::Summary> = host.summaries().collect();
+    let history = |value: fn(&summary::Summary) -> f32| summaries.iter().map(value).collect::<Vec<f32>>();
+    let chart = |series: Vec<Series>, max: Option<f32>| LineChart::new(series, max, host::HISTORY_LEN - 1).view(36);
+    let metric = |label: &str, value: String, chart: Element<'a, Message>| {
+        column![
+            row![text(label.to_owned()).size(12).width(Fill), text(value).size(12)],
+            chart,
+        ].spacing(2)
+    };
+
+    let title = mouse_area(text(TITLE).size(12).width(Fill)).on_press(Message::DragMini);
+    let small_button = |label, message| button(text(label).size(12)).padding([0, 6]).on_press(message).style(button::secondary);
+    let header = if standalone {
+        row![title, small_button("open", Message::OpenMainWindow), small_button("x", Message::ToggleMini)]
+    } else {
+        row![title, small_button("x", Message::ToggleMini)]
+    };
+
+    column![
+        header.spacing(5).align_y(Center),
+        metric("CPU", format!("{}%", summary.cpu_usage),
+            chart(vec![Series { color: MINI_COLORS[0], values: history(|s| s.cpu_usage as f32) }], Some(100.0))),
+        metric("RAM", format!("{:.0}%", summary.ram_percent),
+            chart(vec![Series { color: MINI_COLORS[1], values: history(|s| s.ram_percent) }], Some(100.0))),
+        metric("net", format!("↓ {}  ↑ {}", format_rate(summary.rx_rate), format_rate(summary.tx_rate)),
+            chart(vec![
+                Series { color: MINI_COLORS[0], values: history(|s| s.rx_rate.unwrap_or(0.0) as f32) },
+                Series { color: MINI_COLORS[2], values: history(|s| s.tx_rate.unwrap_or(0.0) as f32) },
+            ], None)),
+        metric("temp", summary.hottest.as_ref().map(|(label, temp)| format!("{} {}°C", label, temp)).unwrap_or("-".to_owned()),
+            chart(vec![Series { color: MINI_COLORS[3], values: history(|s| s.hottest.as_ref().map(|h| h.1 as f32).unwrap_or(0.0)) }], None)),
+    ].spacing(6)
+     .padding(8)
+     .into()
+}
+
+fn replay_controls<'a>(replay: &Replay) -> Element<'a, Message> {
+    let play = button(text(if replay.is_playing() { "Pause" } else { "Play" }).width(60).align_x(Center))
+        .on_press(Message::ReplayToggle)
+        .style(button::primary);
+    let scrubber = slider(0..=(replay.len() as u32 - 1), replay.position() as u32, Message::ReplaySeek);
+    let position = text(format!("{} / {}  {}", replay.position() + 1, replay.len(),
+        format_offset(replay.elapsed_ms()) + " / " + &format_offset(replay.duration_ms())));
+    let speeds = REPLAY_SPEEDS.iter().fold(row![].spacing(5), |speeds, &speed| {
+        speeds.push(button(text(format!("{}x", speed)))
+            .on_press(Message::ReplaySpeed(speed))
+            .style(if speed == replay.speed() { button::primary } else { button::secondary }))
+    });
+    row![play, scrubber, position, speeds].spacing(10).align_y(Center).into()
+}
+
+fn format_offset(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[derive(Debug, Clone, Default)]
 struct Pane {
-    pub data: AllData,
-    pub type_data: TypeData
+    pub type_data: TypeData,
+    pub host: usize,
+    pub cgroup_sort: CgroupSort,
+    pub cgroup_expanded: HashSet<String>,
+    pub socket_state: Option<String>,
+    pub socket_port: String,
+    pub process_expanded: HashSet<u32>,
+    pub process_search: String,
+    pub inspected_pid: Option<u32>,
+    pub process_detail: Option<ProcessDetail>,
+    /// Views shown before the current one, the latest last.
+    pub back: Vec<TypeData>,
+    /// Views left with the back button, the latest last.
+    pub forward: Vec<TypeData>,
 }
 
 impl Pane {
-    fn new(_type_data: TypeData) -> Self {
+    fn new(_type_data: TypeData, _host: usize) -> Self {
         Self {
-            data: AllData::new(),
-            type_data: _type_data
+            type_data: _type_data,
+            host: _host,
+            cgroup_expanded: HashSet::from(["/".to_owned()]),
+            process_expanded: HashSet::from([1]),
+            ..Pane::default()
+        }
+    }
+
+    /// Switches to another view in place, remembering the current one.
+    fn show(&mut self, type_data: TypeData) {
+        if type_data == self.type_data {
+            return;
+        }
+        self.back.push(mem::replace(&mut self.type_data, type_data));
+        if self.back.len() > VIEW_HISTORY {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    fn back(&mut self) {
+        if let Some(previous) = self.back.pop() {
+            self.forward.push(mem::replace(&mut self.type_data, previous));
+        }
+    }
+
+    fn forward(&mut self) {
+        if let Some(next) = self.forward.pop() {
+            self.back.push(mem::replace(&mut self.type_data, next));
+        }
+    }
+
+    /// Takes the details of the inspected process from a new sample.
+    /// Details come from this machine's `/proc`, so only the local host
+    /// has them; panes whose process wasn't read keep what they have.
+    fn update_process_detail(&mut self, details: &HashMap<u32, Option<ProcessDetail>>) {
+        if let Some(detail) = self.inspected_pid.filter(|_| self.host == 0).and_then(|pid| details.get(&pid)) {
+            self.process_detail = detail.clone();
         }
     }
 }
 
-fn view_content<'a>(pane: pane_grid::Pane, cur: &Pane) -> Element<'a, Message> {
-    let controls : iced::widget::Column<'_, Message> = make_column_content(pane, cur);
-    let content =column![controls].spacing(10);
-    scrollable(content).into()
+fn view_content<'a>(pane: pane_grid::Pane, cur: &Pane, hosts: &[Host], queries: &[String], replaying: bool) -> Element<'a, Message> {
+    let host = &hosts[cur.host.min(hosts.len() - 1)];
+    let controls : iced::widget::Column<'_, Message> = if cur.type_data == TypeData::Hosts {
+        hosts_overview(pane, hosts)
+    } else {
+        make_column_content(pane, cur, host, queries, replaying)
+    };
+    let header = if hosts.len() < 2 || cur.type_data == TypeData::Hosts {
+        column![view_selector(pane, cur)]
+    } else if cur.type_data == TypeData::Empty {
+        column![view_selector(pane, cur), host_picker(pane, cur, hosts)]
+    } else {
+        column![view_selector(pane, cur), text("host: ".to_owned() + &host.status())]
+    };
+    // A collector that hung or failed left its last good data in the sample.
+    let stale = host.data().degraded.iter()
+        .filter(|d| cur.type_data.collectors().contains(&d.collector.as_str()))
+        .fold(header.spacing(10), |header, d| {
+            header.push(text(format!("{} data is stale: {}", d.collector, d.error)).color(color!(0xd04040)))
+        });
+    scrollable(column![stale, controls].spacing(10)).into()
 }
 
-fn make_column_content<'a>(pane: pane_grid::Pane, cur: &Pane) -> iced::widget::Column<'a, Message> {
+/// Back and forward buttons and a list switching the view of the pane.
+fn view_selector<'a>(pane: pane_grid::Pane, cur: &Pane) -> iced::widget::Row<'a, Message> {
+    let arrow = |label, enabled: bool, message| {
+        button(text(label).width(20).align_x(Center))
+            .on_press_maybe(enabled.then_some(message))
+            .style(button::secondary)
+    };
+    row![
+        arrow("<", !cur.back.is_empty(), Message::Back(pane)),
+        arrow(">", !cur.forward.is_empty(), Message::Forward(pane)),
+        pick_list(&TypeData::ALL[..], Some(cur.type_data), move |view| Message::Show(pane, view)),
+        button(text("detach")).on_press(Message::Detach(pane)).style(button::secondary),
+    ].spacing(5)
+     .padding([0, 10])
+     .align_y(Center)
+}
 
-    let button = |label, message| {
-        button(text(label).width(Fill).align_x(Center).size(16))
+fn host_picker<'a>(pane: pane_grid::Pane, cur: &Pane, hosts: &[Host]) -> iced::widget::Column<'a, Message> {
+    hosts.iter().enumerate().fold(column![text("host:")].spacing(5).max_width(300).padding(10), |picker, (i, host)| {
+        picker.push(button(text(host.status()).width(Fill).align_x(Center))
             .width(Fill)
-            .padding(10)
-            .on_press(message)
-    };
+            .on_press(Message::SelectHost(pane, i))
+            .style(if i == cur.host { button::primary } else { button::secondary }))
+    })
+}
+
+fn make_column_content<'a>(pane: pane_grid::Pane, cur: &Pane, host: &Host, queries: &[String], replaying: bool) -> iced::widget::Column<'a, Message> {
+    let data = host.data();
 
     match cur.type_data {
         TypeData::Cpu => 
         return column![rich_text([span("CPU").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
         scrollable(column![
-            text(("count logical cores: ").to_owned() + &(&cur.data.cpu_data.count_logical_cores).to_string()),
-            text(("count physical cores: ").to_owned() + &(&cur.data.cpu_data.count_physical_cores).to_string()),
-            text(("cpu brand: ").to_owned() + &(&cur.data.cpu_data.cpu_brand)),
-            text(("cpu architecture: ").to_owned() + &(&cur.data.cpu_data.cpu_arch)),
-            text(("count cpu_usage: ").to_owned() + &(&cur.data.cpu_data.global_cpu_usage).to_string()),
-            text(("cpu frequency: ").to_owned() + &(&cur.data.cpu_data.speed).to_string() + "Mhz"),
-            text(("cpu temperature: ").to_owned() + &(&cur.data.cpu_data.cpu_temp).to_string() + "°C"),]
-        )].spacing(30)
+            text(("count logical cores: ").to_owned() + &(&data.cpu_data.count_logical_cores).to_string()),
+            text(("count physical cores: ").to_owned() + &(&data.cpu_data.count_physical_cores).to_string()),
+            text(("cpu brand: ").to_owned() + &(&data.cpu_data.cpu_brand)),
+            text(("cpu architecture: ").to_owned() + &(&data.cpu_data.cpu_arch)),
+            text(("count cpu_usage: ").to_owned() + &(&data.cpu_data.global_cpu_usage).to_string()),
+            text(("cpu frequency: ").to_owned() + &(&data.cpu_data.speed).to_string() + "Mhz"),
+            text(("cpu temperature: ").to_owned() + &(&data.cpu_data.cpu_temp).to_string() + "°C"),
+            cpu_breakdown(host),]
+        .spacing(5))].spacing(30)
           .padding(10)
           .max_width(900),
     TypeData::Gpu => 
         return column![rich_text([span("GPU").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
         scrollable(column![
-            text(("gpu informations: ").to_owned() + &(&cur.data.gpu_data.gpu_data_vulcan).iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")),]
+            text(("gpu informations: ").to_owned() + &(&data.gpu_data.gpu_data_vulcan).iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")),]
         )].spacing(30)
           .padding(10)
           .max_width(900),
 
     TypeData::Dram => 
           return column![rich_text([span("DRAM").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
-          scrollable(column![
-            text((&cur.data.dram_data.disks).iter().map(|x| "name - ".to_owned() + x.0 + "\n" + &(x.1.to_string())).collect::<Vec<_>>().join("\n*************************\n")),
-            
-            ]
-          )].spacing(30)
+          scrollable(disks_table(&data.dram_data))].spacing(30)
             .padding(10)
             .max_width(900),
 
     TypeData::Ram => 
           return column![rich_text([span("RAM").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
           scrollable(column![
-            text(("total memory: ").to_owned() + &(&cur.data.ram_data.total_memory / 1024000).to_string() + "Mb"),
-            text(("used memory: ").to_owned() + &(&cur.data.ram_data.used_memory / 1024000).to_string() + "Mb"),
-            text(("total swap: ").to_owned() + &(&cur.data.ram_data.total_swap / 1024000).to_string() + "Mb"),
-            text(("free swap: ").to_owned() + &(&cur.data.ram_data.free_swap / 1024000).to_string() + "Mb"),
-            text(("used swap: ").to_owned() + &(&cur.data.ram_data.used_swap / 1024000).to_string() + "Mb"),
-            text(("available memory: ").to_owned() + &(&cur.data.ram_data.available_memory / 1024000).to_string() + "Mb"),]
-          )].spacing(30)
+            text(("total memory: ").to_owned() + &(&data.ram_data.total_memory / 1024000).to_string() + "Mb"),
+            text(("used memory: ").to_owned() + &(&data.ram_data.used_memory / 1024000).to_string() + "Mb"),
+            text(("total swap: ").to_owned() + &(&data.ram_data.total_swap / 1024000).to_string() + "Mb"),
+            text(("free swap: ").to_owned() + &(&data.ram_data.free_swap / 1024000).to_string() + "Mb"),
+            text(("used swap: ").to_owned() + &(&data.ram_data.used_swap / 1024000).to_string() + "Mb"),
+            text(("available memory: ").to_owned() + &(&data.ram_data.available_memory / 1024000).to_string() + "Mb"),
+            meminfo_breakdown(&data.ram_data.meminfo),]
+          .spacing(5))].spacing(30)
             .padding(10)
             .max_width(900),
 
     TypeData::Os => 
             return column![rich_text([span("OS").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
             scrollable(column![
-            text(("os type: ").to_owned() + &(&cur.data.os_data.os_type).to_string()),
-            text(("name os: ").to_owned() + &(&cur.data.os_data.name_os.clone().unwrap_or("unknown".to_owned()))),
-            text(("kernel version: ").to_owned() + &(&cur.data.os_data.kernel_version.clone().unwrap_or("unknown".to_owned()))),
-            text(("os version: ").to_owned() + &(&cur.data.os_data.os_version.clone().unwrap_or("unknown".to_owned()))),
-            text(("distribution: ").to_owned() + &(&cur.data.os_data.distribution)),
-            text(("host name: ").to_owned() + &(&cur.data.os_data.host_name.clone().unwrap_or("unknown".to_owned()))),
-            text(("cpu arch: ").to_owned() + &(&cur.data.os_data.cpu_arch)),]
+            text(("os type: ").to_owned() + &(&data.os_data.os_type).to_string()),
+            text(("name os: ").to_owned() + &(&data.os_data.name_os.clone().unwrap_or("unknown".to_owned()))),
+            text(("kernel version: ").to_owned() + &(&data.os_data.kernel_version.clone().unwrap_or("unknown".to_owned()))),
+            text(("os version: ").to_owned() + &(&data.os_data.os_version.clone().unwrap_or("unknown".to_owned()))),
+            text(("distribution: ").to_owned() + &(&data.os_data.distribution)),
+            text(("host name: ").to_owned() + &(&data.os_data.host_name.clone().unwrap_or("unknown".to_owned()))),
+            text(("cpu arch: ").to_owned() + &(&data.os_data.cpu_arch)),
+            text(("uptime: ").to_owned() + &format_age(data.os_data.uptime)),
+            text(format!("load average: {:.2} {:.2} {:.2}", data.os_data.load_average[0], data.os_data.load_average[1], data.os_data.load_average[2])),]
             )].spacing(30)
               .padding(10)
               .max_width(900),
 
     TypeData::Network => 
               return column![rich_text([span("Network").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
-              scrollable(column![
-                text(("network informations: ").to_owned() + &(&cur.data.network_data.data_network).iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")),]
-              )].spacing(30)
+              scrollable(column![interface_cards(&data.network_data), wireless_links(host)].spacing(20))].spacing(30)
                 .padding(10)
                 .max_width(900),                  
-    _ =>
-        return column![
-        button(
-            "CPU",
-            Message::Data(pane, TypeData::Cpu)
-        ).style(button::primary),
-        button(
-            "GPU",
-            Message::Data(pane, TypeData::Gpu)
-        ).style(button::primary),
-        button(
-            "DRAM",
-            Message::Data(pane, TypeData::Dram)
-        ).style(button::primary),
-        button(
-            "RAM",
-            Message::Data(pane, TypeData::Ram)
-        ).style(button::primary),
-        button(
-            "OS",
-            Message::Data(pane, TypeData::Os)
-        ).style(button::primary),
-        button(
-            "NETWORK",
-            Message::Data(pane, TypeData::Network)
-        ).style(button::primary)
-        ].spacing(15)
-         .max_width(300)
-         .padding(10),
+    TypeData::Cgroups => cgroups_view(pane, cur, host),
+    TypeData::Connections => connections_view(pane, cur, data),
+    TypeData::Sensors => sensors_view(data),
+    TypeData::Pressure => pressure_view(host),
+    TypeData::Kernel => kernel_view(host),
+    TypeData::Blocks => blocks_view(&data.block_data),
+    TypeData::Routes => routes_view(&data.routing_data),
+    TypeData::Sessions => sessions_view(host),
+    TypeData::Processes => processes_view(pane, cur, host, queries),
+    TypeData::ProcessDetail => process_detail_view(cur, host, replaying),
+    _ => dashboard(pane, host),
     }
 
 
 }
 
+/// How many of the fullest disks the overview shows.
+const DASHBOARD_DISKS: usize = 3;
+
+/// The headline numbers of a host as tiles; each one opens its pane, the
+/// alerts tile the one of the most severe alert.
+fn dashboard<'a>(pane: pane_grid::Pane, host: &Host) -> iced::widget::Column<'a, Message> {
+    let data = host.data();
+    let summary = host.summary().unwrap_or_default();
+    let tile = |label: &str, lines: Vec<String>, alert: Option<Severity>, target: Option<TypeData>| {
+        let title = span(label.to_owned()).font(Font { weight: font::Weight::Bold, ..Font::default() });
+        let title = match alert {
+            Some(Severity::Critical) => title.color(color!(0xd04040)),
+            Some(Severity::Warning) => title.color(color!(0xe0a030)),
+            None => title,
+        };
+        let card = lines.into_iter().fold(column![rich_text([title])].spacing(3), |card, line| card.push(text(line).size(18)));
+        button(container(card).width(Fill))
+            .width(200)
+            .padding(10)
+            .on_press_maybe(target.map(|target| Message::Show(pane, target)))
+            .style(button::secondary)
+    };
+    let warning_if = |warn: bool| if warn { Some(Severity::Warning) } else { None };
+
+    let mut disks: Vec<(&String, &structs::Disk)> = data.dram_data.disks.iter().filter(|(_, d)| d.total_space > 0).collect();
+    disks.sort_by(|a, b| b.1.used_percent().total_cmp(&a.1.used_percent()));
+    let load = data.os_data.load_average;
+    let alerts = if summary.alerts.is_empty() {
+        vec!["none".to_owned()]
+    } else {
+        summary.alerts.iter().map(|a| a.message.clone()).collect()
+    };
+    let gpus = if data.gpu_data.gpu_data_vulcan.is_empty() {
+        vec!["none".to_owned()]
+    } else {
+        data.gpu_data.gpu_data_vulcan.clone()
+    };
+
+    let tiles = row![
+        tile("CPU", vec![format!("{}%", summary.cpu_usage)], warning_if(summary.cpu_usage >= summary::CPU_WARNING), Some(TypeData::Cpu)),
+        tile("Load", vec![format!("{:.2} {:.2} {:.2}", load[0], load[1], load[2])], None, Some(TypeData::Os)),
+        tile("RAM / swap", vec![format!("{:.0}% / {:.0}%", summary.ram_percent, summary.swap_percent)],
+            warning_if(summary.ram_percent >= summary::RAM_WARNING), Some(TypeData::Ram)),
+        tile("Disks", disks.iter().take(DASHBOARD_DISKS).map(|(name, d)| format!("{} {:.0}%", name, d.used_percent())).collect(),
+            disks.first().and_then(|(_, d)| disk_severity(d.used_percent())), Some(TypeData::Dram)),
+        tile("Network", vec![format!("in {}", format_rate(summary.rx_rate)), format!("out {}", format_rate(summary.tx_rate))], None, Some(TypeData::Network)),
+        tile("Hottest sensor", vec![summary.hottest.as_ref().map(|(label, temp)| format!("{} {}°C", label, temp)).unwrap_or("-".to_owned())],
+            None, Some(TypeData::Sensors)),
+        tile("GPU", gpus, None, Some(TypeData::Gpu)),
+        tile("Uptime", vec![format_age(data.os_data.uptime)], None, Some(TypeData::Os)),
+        tile("Alerts", alerts, summary.severity(),
+            summary.worst_alert().and_then(|a| TypeData::for_alert(&a.kind))),
+    ].spacing(10).wrap();
+
+    column![
+        rich_text([span("Overview").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
+        tiles,
+        text("open beside:"),
+        view_buttons(pane),
+    ].spacing(20)
+     .padding(10)
+}
+
+/// The buttons opening every kind of pane.
+fn view_buttons<'a>(pane: pane_grid::Pane) -> iced::widget::Column<'a, Message> {
+    TypeData::ALL.iter().filter(|view| **view != TypeData::Empty).fold(column![].spacing(15).max_width(300).padding(10), |buttons, view| {
+        buttons.push(button(text(view.to_string()).width(Fill).align_x(Center).size(16))
+            .width(Fill)
+            .padding(10)
+            .on_press(Message::Data(pane, *view))
+            .style(button::primary))
+    })
+}
+
+fn hosts_overview<'a>(pane: pane_grid::Pane, hosts: &[Host]) -> iced::widget::Column<'a, Message> {
+    let cell = |value: String, width: u16| text(value).width(width);
+    let header = row![
+        cell("host".to_owned(), 220),
+        cell("CPU".to_owned(), 60),
+        cell("RAM".to_owned(), 60),
+        cell("fullest disk".to_owned(), 160),
+        cell("hottest sensor".to_owned(), 160),
+        cell("net in / out".to_owned(), 180),
+        cell("alerts".to_owned(), 200),
+    ].spacing(10).padding([0, 10]);
+
+    hosts.iter().enumerate().fold(column![
+        rich_text([span("Hosts").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
+        header,
+    ].spacing(10).padding(10), |overview, (i, host)| {
+        let summary = host.summary().unwrap_or_default();
+        let alerts = if !host.connected {
+            "disconnected".to_owned()
+        } else if summary.alerts.is_empty() {
+            "ok".to_owned()
+        } else {
+            summary.alerts.iter().map(|a| a.message.clone()).collect::<Vec<_>>().join(", ")
+        };
+        let style = match (host.connected, summary.severity()) {
+            (false, _) | (_, Some(Severity::Critical)) => button::danger,
+            (true, Some(Severity::Warning)) => button::primary,
+            (true, None) => button::secondary,
+        };
+        let line = row![
+            cell(host.name.clone(), 220),
+            cell(summary.cpu_usage.to_string() + "%", 60),
+            cell(format!("{:.0}%", summary.ram_percent), 60),
+            cell(summary.worst_disk.map(|(name, full)| format!("{} {:.0}%", name, full)).unwrap_or("-".to_owned()), 160),
+            cell(summary.hottest.map(|(label, temp)| format!("{} {}°C", label, temp)).unwrap_or("-".to_owned()), 160),
+            cell(format_rate(summary.rx_rate) + " / " + &format_rate(summary.tx_rate), 180),
+            cell(alerts, 200),
+        ].spacing(10);
+        overview.push(button(line).width(Fill).on_press(Message::OpenHost(pane, i)).style(style))
+    })
+}
+
+/// What the cgroup rows need besides the node itself.
+struct CgroupRows<'a> {
+    pane: pane_grid::Pane,
+    sort: CgroupSort,
+    expanded: &'a HashSet<String>,
+    cpu_percent: HashMap<String, f64>,
+}
+
+fn cgroups_view<'a>(pane: pane_grid::Pane, cur: &Pane, host: &Host) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Cgroups").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let Some(root) = &host.data().cgroup_data.root else {
+        return column![title, text("no cgroup v2 hierarchy at ".to_owned() + cgroup::CGROUP_ROOT)].spacing(30).padding(10);
+    };
+
+    // CPU usage is the growth of usage_usec since the previous sample.
+    let mut cpu_percent: HashMap<String, f64> = HashMap::new();
+    if let (Some(prev), Some(current)) = (host.previous(), host.latest()) {
+        let elapsed_usec = current.timestamp_ms.saturating_sub(prev.timestamp_ms) as f64 * 1000.0;
+        if let Some(prev_root) = &prev.data.cgroup_data.root {
+            let mut prev_usage: HashMap<&str, u64> = HashMap::new();
+            prev_root.walk(&mut |node| { prev_usage.insert(&node.path, node.cpu_usage_usec); });
+            root.walk(&mut |node| {
+                if let Some(before) = prev_usage.get(node.path.as_str()) {
+                    if elapsed_usec > 0.0 {
+                        cpu_percent.insert(node.path.clone(), node.cpu_usage_usec.saturating_sub(*before) as f64 * 100.0 / elapsed_usec);
+                    }
+                }
+            });
+        }
+    }
+
+    let sorts = [
+        ("name", CgroupSort::Name),
+        ("CPU", CgroupSort::Cpu),
+        ("memory", CgroupSort::Memory),
+        ("I/O", CgroupSort::Io),
+        ("pids", CgroupSort::Pids),
+    ].into_iter().fold(row![text("sort by:")].spacing(5).align_y(Center), |sorts, (label, sort)| {
+        sorts.push(button(text(label))
+            .on_press(Message::CgroupSort(pane, sort))
+            .style(if sort == cur.cgroup_sort { button::primary } else { button::secondary }))
+    });
+    let header = row![
+        text("cgroup").width(320),
+        text("CPU").width(70),
+        text("throttled").width(110),
+        text("memory / high / max").width(220),
+        text("swap").width(90),
+        text("I/O read / write").width(170),
+        text("pids").width(50),
+        text("PSI some avg10 cpu / mem / io").width(220),
+    ].spacing(10);
+
+    let rows = CgroupRows {
+        pane,
+        sort: cur.cgroup_sort,
+        expanded: &cur.cgroup_expanded,
+        cpu_percent,
+    };
+    let mut table = column![header].spacing(4);
+    table = push_cgroup(table, root, 0, &rows);
+    column![title, sorts, scrollable(table).direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::default()))]
+        .spacing(20)
+        .padding(10)
+}
+
+fn push_cgroup<'a>(table: iced::widget::Column<'a, Message>, node: &CgroupNode, depth: usize, rows: &CgroupRows) -> iced::widget::Column<'a, Message> {
+    let expanded = rows.expanded.contains(&node.path);
+    let marker = if node.children.is_empty() { "  " } else if expanded { "▾ " } else { "▸ " };
+    let name = button(text("    ".repeat(depth) + marker + &node.name).size(14))
+        .padding(0)
+        .style(button::text)
+        .on_press(Message::CgroupToggle(rows.pane, node.path.clone()));
+    let opt = |value: Option<u64>| value.map(|v| format_bytes(v as f64)).unwrap_or("-".to_owned());
+    let psi = |pressure: &Option<pressure::Pressure>| pressure.as_ref().map(|p| format!("{:.1}", p.some.avg10)).unwrap_or("-".to_owned());
+    let line = row![
+        name.width(320),
+        text(rows.cpu_percent.get(&node.path).map(|p| format!("{:.1}%", p)).unwrap_or("-".to_owned())).width(70),
+        text(format!("{:.1}s ({})", node.throttled_usec as f64 / 1_000_000.0, node.nr_throttled)).width(110),
+        text(opt(node.memory_current) + " / " + &opt(node.memory_high) + " / " + &opt(node.memory_max)).width(220),
+        text(opt(node.swap_current)).width(90),
+        text(format_bytes(node.io_rbytes as f64) + " / " + &format_bytes(node.io_wbytes as f64)).width(170),
+        text(node.pids_current.map(|p| p.to_string()).unwrap_or("-".to_owned())).width(50),
+        text(psi(&node.pressure.cpu) + " / " + &psi(&node.pressure.memory) + " / " + &psi(&node.pressure.io)).width(220),
+    ].spacing(10);
+    let mut table = table.push(line);
+    if expanded {
+        let mut children: Vec<&CgroupNode> = node.children.iter().collect();
+        match rows.sort {
+            CgroupSort::Name => {}
+            CgroupSort::Cpu => children.sort_by(|a, b| {
+                let cpu = |n: &CgroupNode| rows.cpu_percent.get(&n.path).copied().unwrap_or(0.0);
+                cpu(b).total_cmp(&cpu(a))
+            }),
+            CgroupSort::Memory => children.sort_by_key(|n| std::cmp::Reverse(n.memory_current.unwrap_or(0))),
+            CgroupSort::Io => children.sort_by_key(|n| std::cmp::Reverse(n.io_rbytes + n.io_wbytes)),
+            CgroupSort::Pids => children.sort_by_key(|n| std::cmp::Reverse(n.pids_current.unwrap_or(0))),
+        }
+        for child in children {
+            table = push_cgroup(table, child, depth + 1, rows);
+        }
+    }
+    table
+}
+
+/// Own and subtree CPU %, RSS and thread count of a process.
+#[derive(Clone, Copy, Default)]
+struct ProcessUsage {
+    cpu: f32,
+    rss: u64,
+    threads: u64,
+}
+
+struct ProcessRows<'a> {
+    pane: pane_grid::Pane,
+    expanded: &'a HashSet<u32>,
+    children: HashMap<u32, Vec<&'a ProcessInfo>>,
+    cpu_percent: HashMap<u32, f32>,
+    subtree: HashMap<u32, ProcessUsage>,
+    /// While searching: the matches and their ancestors, all shown expanded.
+    visible: Option<(HashSet<u32>, HashSet<u32>)>,
+}
+
+impl ProcessRows<'_> {
+
+    /// Sums the usage of `pid` and its descendants into `subtree`.
+    fn sum_subtree(&mut self, process: &ProcessInfo) -> ProcessUsage {
+        let mut usage = ProcessUsage {
+            cpu: self.cpu_percent.get(&process.pid).copied().unwrap_or(0.0),
+            rss: process.rss,
+            threads: process.threads,
+        };
+        for child in self.children.get(&process.pid).cloned().unwrap_or_default() {
+            let child = self.sum_subtree(child);
+            usage.cpu += child.cpu;
+            usage.rss += child.rss;
+            usage.threads += child.threads;
+        }
+        self.subtree.insert(process.pid, usage);
+        usage
+    }
+}
+
+fn processes_view<'a>(pane: pane_grid::Pane, cur: &Pane, host: &Host, queries: &[String]) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Processes").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let data = &host.data().processes_data;
+    let cpu_percent = match (host.previous(), host.latest()) {
+        (Some(prev), Some(current)) => data.cpu_percent(&prev.data.processes_data,
+            current.data.cpu_data.elapsed_ticks(&prev.data.cpu_data)),
+        _ => HashMap::new(),
+    };
+
+    let mut rows = ProcessRows {
+        pane,
+        expanded: &cur.process_expanded,
+        children: data.children(),
+        cpu_percent,
+        subtree: HashMap::new(),
+        visible: None,
+    };
+    let roots = rows.children.get(&0).cloned().unwrap_or_default();
+    for root in &roots {
+        rows.sum_subtree(root);
+    }
+
+    let query = Query::parse(&cur.process_search);
+    if let Some(query) = query.as_ref().ok().filter(|q| !q.is_empty()) {
+        let matches: HashSet<u32> = data.processes.iter()
+            .filter(|p| query.matches(p, rows.cpu_percent.get(&p.pid).copied().unwrap_or(0.0)))
+            .map(|p| p.pid)
+            .collect();
+        let mut ancestors: HashSet<u32> = HashSet::new();
+        for pid in &matches {
+            let mut parent = data.get(*pid).map(|p| p.ppid);
+            while let Some(ppid) = parent.filter(|ppid| *ppid != 0 && ancestors.insert(*ppid)) {
+                parent = data.get(ppid).map(|p| p.ppid);
+            }
+        }
+        rows.visible = Some((matches, ancestors));
+    }
+
+    let header = row![
+        text("process").width(360),
+        text("pid").width(70),
+        text("user").width(90),
+        text("state").width(50),
+        text("CPU / tree").width(130),
+        text("RSS / tree").width(190),
+        text("threads / tree").width(110),
+    ].spacing(10);
+    let mut table = column![header].spacing(2);
+    for root in sorted_processes(roots, &rows) {
+        table = push_process(table, root, 0, &rows);
+    }
+    let status = match (&query, &rows.visible) {
+        (Err(e), _) => text(e.clone()).color(color!(0xff0000)),
+        (Ok(_), Some((matches, _))) => text(format!("{} of {} processes", matches.len(), data.processes.len())),
+        (Ok(_), None) => text(format!("{} processes", data.processes.len())),
+    };
+    let saved = queries.iter().enumerate().fold(row![text("saved:")].spacing(5).align_y(Center), |saved, (i, query)| {
+        saved.push(row![
+            button(text(query.clone()).size(13))
+                .on_press(Message::ProcessSearch(pane, query.clone()))
+                .style(if *query == cur.process_search.trim() { button::primary } else { button::secondary }),
+            button(text("×").size(13)).on_press(Message::DeleteQuery(i)).style(button::text),
+        ])
+    });
+    column![
+        title,
+        row![
+            text("filter:"),
+            text_input("e.g. java, user:ci cpu>20 mem>1GiB name~java state:D", &cur.process_search)
+                .on_input(move |search| Message::ProcessSearch(pane, search))
+                .width(400),
+            button(text("save")).on_press(Message::SaveQuery(pane)).style(button::secondary),
+            status,
+        ].spacing(10).align_y(Center),
+        saved.wrap(),
+        scrollable(table).direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::default())),
+    ].spacing(20)
+     .padding(10)
+}
+
+/// The inspector opened by clicking a pid in the process tree.
+fn process_detail_view<'a>(cur: &Pane, host: &Host, replaying: bool) -> iced::widget::Column<'a, Message> {
+    let pid = cur.inspected_pid.unwrap_or(0);
+    let title = rich_text([span(format!("Process {}", pid)).color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let Some(detail) = &cur.process_detail else {
+        let note = if cur.host != 0 {
+            "details are read from /proc of this machine, so they are not available for remote hosts"
+        } else if host.data().processes_data.get(pid).is_none() {
+            "the process has exited"
+        } else if replaying {
+            "details are read live from /proc with the next sample, so they are not available while replaying"
+        } else {
+            "loading… details are read from /proc with the next sample"
+        };
+        return column![title, text(note)].spacing(30).padding(10);
+    };
+    let info = &detail.info;
+    let section = |name: &str| text(name.to_owned()).font(Font { weight: font::Weight::Bold, ..Font::default() });
+    let lines = |header: &str, lines: Vec<String>| {
+        lines.into_iter().fold(column![section(header)].spacing(2), |column, line| column.push(text(line).size(13)))
+    };
+    let unreadable = || vec!["not readable (permission denied)".to_owned()];
+    let ids = |ids: &[u32]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(" / ");
+
+    column![
+        title,
+        lines("process", vec![
+            format!("name: {}  state: {}  parent: {}", info.name, info.state, info.ppid),
+            format!("command line: {}", if info.cmdline.is_empty() { "-" } else { &info.cmdline }),
+            format!("executable: {}", detail.exe.clone().unwrap_or("-".to_owned())),
+            format!("working directory: {}", detail.cwd.clone().unwrap_or("-".to_owned())),
+            format!("user: {}  uid (real / effective / saved / fs): {}", info.user, ids(&detail.uids)),
+            format!("gid (real / effective / saved / fs): {}  groups: {}", ids(&detail.gids), ids(&detail.groups)),
+            format!("nice: {}  priority: {}  policy: {}  realtime priority: {}", info.nice, info.priority, detail.policy, detail.rt_priority),
+            format!("memory: {} resident, {} virtual", format_bytes(info.rss as f64), format_bytes(info.vsize as f64)),
+        ]),
+        lines("memory (smaps_rollup)", detail.smaps_rollup.as_ref()
+            .map(|smaps| smaps.iter().map(|(key, bytes)| format!("{}: {}", key, format_bytes(*bytes as f64))).collect())
+            .unwrap_or_else(unreadable)),
+        lines("I/O", detail.io.as_ref()
+            .map(|io| io.iter().map(|(key, value)| format!("{}: {}", key, value)).collect())
+            .unwrap_or_else(unreadable)),
+        lines(&format!("threads ({})", detail.threads.len()),
+            detail.threads.iter().map(|(tid, name, state)| format!("{} {} {}", tid, state, name)).collect()),
+        lines(&format!("open files ({})", detail.fds.as_ref().map(|f| f.len()).unwrap_or(0)), detail.fds.as_ref()
+            .map(|fds| fds.iter().map(|(fd, target)| format!("{} -> {}", fd, target)).collect())
+            .unwrap_or_else(unreadable)),
+        lines("limits (soft / hard)", detail.limits.iter()
+            .map(|(name, soft, hard, unit)| format!("{}: {} / {} {}", name, soft, hard, unit)).collect()),
+        lines("environment", detail.environment.clone().unwrap_or_else(unreadable)),
+    ].spacing(20)
+     .padding(10)
+     .max_width(900)
+}
+
+/// Busiest subtrees first.
+fn sorted_processes<'a>(mut processes: Vec<&'a ProcessInfo>, rows: &ProcessRows) -> Vec<&'a ProcessInfo> {
+    let cpu = |p: &ProcessInfo| rows.subtree.get(&p.pid).map(|u| u.cpu).unwrap_or(0.0);
+    processes.sort_by(|a, b| cpu(b).total_cmp(&cpu(a)).then(a.pid.cmp(&b.pid)));
+    processes
+}
+
+fn push_process<'a>(table: iced::widget::Column<'a, Message>, process: &ProcessInfo, depth: usize, rows: &ProcessRows) -> iced::widget::Column<'a, Message> {
+    let (is_match, expanded) = match &rows.visible {
+        Some((matches, ancestors)) => {
+            if !matches.contains(&process.pid) && !ancestors.contains(&process.pid) {
+                return table;
+            }
+            (matches.contains(&process.pid), ancestors.contains(&process.pid))
+        }
+        None => (false, rows.expanded.contains(&process.pid)),
+    };
+    let children = rows.children.get(&process.pid).cloned().unwrap_or_default();
+    let marker = if children.is_empty() { "  " } else if expanded { "▾ " } else { "▸ " };
+    let label = text("    ".repeat(depth) + marker + &process.name).size(14);
+    let name = button(if is_match { label.color(color!(0x4070d0)) } else { label })
+        .padding(0)
+        .style(button::text)
+        .on_press(Message::ProcessToggle(rows.pane, process.pid));
+    let own = rows.cpu_percent.get(&process.pid).map(|c| format!("{:.1}", c)).unwrap_or("-".to_owned());
+    let tree = rows.subtree.get(&process.pid).copied().unwrap_or_default();
+    let pid = button(text(process.pid.to_string()).size(14))
+        .padding(0)
+        .style(button::text)
+        .on_press(Message::InspectProcess(rows.pane, process.pid));
+    let line = row![
+        name.width(360),
+        pid.width(70),
+        text(process.user.clone()).width(90),
+        text(process.state.clone()).width(50),
+        text(format!("{} / {:.1}%", own, tree.cpu)).width(130),
+        text(format_bytes(process.rss as f64) + " / " + &format_bytes(tree.rss as f64)).width(190),
+        text(format!("{} / {}", process.threads, tree.threads)).width(110),
+    ].spacing(10);
+    let mut table = table.push(line);
+    if expanded {
+        for child in sorted_processes(children, rows) {
+            table = push_process(table, child, depth + 1, rows);
+        }
+    }
+    table
+}
+
+fn connections_view<'a>(pane: pane_grid::Pane, cur: &Pane, data: &AllData) -> iced::widget::Column<'a, Message> {
+    let states: Vec<String> = std::iter::once(ALL_STATES).chain(sockets::STATES).map(|s| s.to_owned()).collect();
+    let selected = cur.socket_state.clone().unwrap_or(ALL_STATES.to_owned());
+    let port: Option<u16> = cur.socket_port.trim().parse().ok();
+    let filters = row![
+        text("state:"),
+        pick_list(states, Some(selected), move |state| Message::SocketState(pane, state)),
+        text("port:"),
+        text_input("any", &cur.socket_port).on_input(move |port| Message::SocketPort(pane, port)).width(100),
+    ].spacing(10).align_y(Center);
+
+    let matching: Vec<&sockets::SocketEntry> = data.socket_data.sockets.iter()
+        .filter(|s| cur.socket_state.as_ref().is_none_or(|state| &s.state == state))
+        .filter(|s| port.is_none_or(|port| s.local_port == port || s.remote_port == port))
+        .collect();
+
+    let header = row![
+        text("proto").width(50),
+        text("local address").width(260),
+        text("remote address").width(260),
+        text("state").width(110),
+        text("send-q").width(60),
+        text("recv-q").width(60),
+        text("process").width(200),
+    ].spacing(10);
+    let table = matching.iter().fold(column![header].spacing(4), |table, s| {
+        let process = match (&s.pid, &s.process) {
+            (Some(pid), Some(name)) => format!("{} ({})", name, pid),
+            _ => format!("- (uid {})", s.uid),
+        };
+        table.push(row![
+            text(s.protocol.clone()).width(50),
+            text(s.local()).width(260),
+            text(s.remote()).width(260),
+            text(s.state.clone()).width(110),
+            text(s.tx_queue.to_string()).width(60),
+            text(s.rx_queue.to_string()).width(60),
+            text(process).width(200),
+        ].spacing(10))
+    });
+
+    column![
+        rich_text([span("Connections").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]),
+        filters,
+        text(format!("{} of {} sockets", matching.len(), data.socket_data.sockets.len())),
+        scrollable(table).direction(scrollable::Direction::Horizontal(scrollable::Scrollbar::default())),
+    ].spacing(20)
+     .padding(10)
+}
+
+fn sensors_view<'a>(data: &AllData) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Sensors").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    if data.hwmon_data.chips.is_empty() {
+        return column![title, text("no fan, voltage, current or power sensors found")].spacing(30).padding(10);
+    }
+    let limit = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or("-".to_owned());
+    let chips = data.hwmon_data.chips.iter().fold(column![].spacing(20), |chips, chip| {
+        let name = span(chip.name.clone() + " (" + &chip.path + ")").font(Font { weight: font::Weight::Bold, ..Font::default() });
+        let header = row![
+            text("sensor").width(200),
+            text("value").width(120),
+            text("min").width(80),
+            text("max").width(80),
+            text("alarm").width(60),
+        ].spacing(10);
+        let table = chip.sensors.iter().fold(column![header].spacing(4), |table, sensor| {
+            let cell = |value: String| rich_text([if sensor.alarm { span(value).color(color!(0xff0000)) } else { span(value) }]);
+            table.push(row![
+                cell(sensor.label.clone()).width(200),
+                cell(format!("{:.2} {}", sensor.value, sensor.kind.unit())).width(120),
+                cell(limit(sensor.min)).width(80),
+                cell(limit(sensor.max)).width(80),
+                cell(if sensor.alarm { "ALARM".to_owned() } else { "-".to_owned() }).width(60),
+            ].spacing(10))
+        });
+        chips.push(column![
+            rich_text([if chip.has_alarm() { name.color(color!(0xff0000)) } else { name }]),
+            table,
+        ].spacing(8))
+    });
+    column![title, scrollable(chips)].spacing(30).padding(10).max_width(900)
+}
+
+/// A horizontal bar split in proportion to the weights.
+fn stacked_bar<'a>(segments: impl Iterator<Item = (f64, iced::Color)>, height: u16) -> iced::widget::Row<'a, Message> {
+    let segments: Vec<(f64, iced::Color)> = segments.filter(|(weight, _)| *weight > 0.0).collect();
+    let total: f64 = segments.iter().map(|(weight, _)| weight).sum();
+    segments.into_iter().fold(row![].height(height).width(Fill), |bar, (weight, fill)| {
+        let portion = ((weight * 1000.0 / total).round() as u16).max(1);
+        bar.push(container(text("")).width(FillPortion(portion)).height(Fill)
+            .style(move |_| container::Style { background: Some(fill.into()), ..Default::default() }))
+    })
+}
+
+const CPU_TIME_COLORS: [iced::Color; 8] = [
+    color!(0x40a040),
+    color!(0x80c080),
+    color!(0xd04040),
+    color!(0xdddddd),
+    color!(0xe08030),
+    color!(0x9050c0),
+    color!(0xd070b0),
+    color!(0x404040),
+];
+
+/// Stacked user/nice/system/idle/iowait/irq/softirq/steal shares between
+/// the two latest samples, for the whole machine and then per core.
+fn cpu_breakdown<'a>(host: &Host) -> iced::widget::Column<'a, Message> {
+    let (Some(prev), Some(cur)) = (host.previous(), host.latest()) else {
+        return column![text("CPU time breakdown: waiting for a second sample")];
+    };
+    let (prev, cur) = (&prev.data.cpu_data, &cur.data.cpu_data);
+    let Some(total) = cur.total_times.shares_since(&prev.total_times) else {
+        return column![];
+    };
+    let legend = structs::CpuTimes::NAMES.iter().enumerate().fold(row![].spacing(10), |legend, (i, name)| {
+        legend.push(rich_text([span("■ ").color(CPU_TIME_COLORS[i]), span(format!("{} {:.1}%", name, total[i]))]))
+    });
+    let bar = |shares: [f32; 8]| stacked_bar(shares.iter().zip(CPU_TIME_COLORS).map(|(share, fill)| (*share as f64, fill)), 14);
+    let cores = cur.core_times.iter().zip(&prev.core_times).enumerate().fold(column![].spacing(4), |cores, (i, (now, before))| {
+        match now.shares_since(before) {
+            Some(shares) => cores.push(row![
+                text(format!("cpu{}", i)).width(50).size(13),
+                bar(shares),
+                text(format!("us {:.0}% sy {:.0}% io {:.0}% st {:.0}%", shares[0] + shares[1], shares[2], shares[4], shares[7])).width(200).size(13),
+            ].spacing(10).align_y(Center)),
+            None => cores,
+        }
+    });
+    column![
+        text("CPU time breakdown:"),
+        bar(total),
+        legend.wrap(),
+        cores,
+    ].spacing(8)
+     .padding([20, 0])
+}
+
+/// One row per mounted filesystem with byte and inode usage side by side,
+/// each coloured once it gets close to exhaustion.
+fn disks_table<'a>(dram: &structs::DramData) -> iced::widget::Column<'a, Message> {
+    let usage = |full: Option<f32>| -> iced::widget::Text<'a> {
+        let cell = text(full.map(|f| format!("{:.1}%", f)).unwrap_or("-".to_owned())).width(70);
+        match full.and_then(disk_severity) {
+            Some(Severity::Critical) => cell.color(color!(0xff0000)),
+            Some(Severity::Warning) => cell.color(color!(0xe08000)),
+            None => cell,
+        }
+    };
+    let header = row![
+        text("device").width(160),
+        text("mount point").width(160),
+        text("type").width(70),
+        text("size").width(90),
+        text("used").width(70),
+        text("inodes").width(70),
+    ].spacing(10);
+    let mut disks: Vec<(&String, &structs::Disk)> = dram.disks.iter().collect();
+    disks.sort_by(|a, b| a.1.mount_point.cmp(&b.1.mount_point));
+    disks.into_iter().fold(column![header].spacing(8), |table, (name, disk)| {
+        let details = match &disk.stats {
+            Some(stats) => format!("{} {}, {} of {} inodes used, block size {}, max name length {}, reserved for root {}{}{}",
+                disk.disk_type, disk.file_system, stats.used_inodes(), stats.total_inodes, format_bytes(stats.block_size as f64),
+                stats.max_name_length, format_bytes(stats.reserved_bytes as f64),
+                if disk.is_read_only { ", read-only" } else { "" },
+                if disk.is_removable { ", removable" } else { "" }),
+            None => format!("{} {}, statvfs unavailable", disk.disk_type, disk.file_system),
+        };
+        table.push(column![
+            row![
+                text(name.clone()).width(160),
+                text(disk.mount_point.clone()).width(160),
+                text(disk.file_system.clone()).width(70),
+                text(format!("{} Mb", disk.total_space)).width(90),
+                usage(Some(disk.used_percent())),
+                usage(disk.inode_percent()),
+            ].spacing(10),
+            text(details).size(12),
+        ].spacing(2))
+    })
+}
+
+/// The disk → partition → dm/md → filesystem tree, one line per device
+/// indented by depth, with degraded md arrays in red.
+fn blocks_view<'a>(block: &blockdev::BlockData) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Block devices").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let mut lines: Vec<Element<'a, Message>> = Vec::new();
+    for disk in &block.disks {
+        disk.walk(0, &mut |dev, depth| {
+            let mut attributes: Vec<String> = vec![dev.kind.label().to_owned(), format_bytes(dev.size as f64)];
+            attributes.extend(dev.model.clone());
+            attributes.extend(dev.serial.as_ref().map(|s| "serial ".to_owned() + s));
+            if dev.kind == blockdev::BlockKind::Disk {
+                attributes.push(if dev.rotational { "rotational" } else { "non-rotational" }.to_owned());
+            }
+            attributes.extend(dev.scheduler.as_ref().map(|s| "scheduler ".to_owned() + s));
+            attributes.extend(dev.dm_name.clone());
+            if dev.slaves.len() > 1 {
+                attributes.push("on ".to_owned() + &dev.slaves.join(" + "));
+            }
+            if dev.read_only {
+                attributes.push("read-only".to_owned());
+            }
+            attributes.extend(dev.mounts.iter().map(|(point, fs)| format!("{} on {}", fs, point)));
+
+            let name = span(dev.name.clone()).font(Font { weight: font::Weight::Bold, ..Font::default() });
+            let line = match &dev.raid {
+                Some(raid) => {
+                    let state = format!("  {} {} [{}]{}{}", raid.level, raid.state, raid.status,
+                        if raid.is_degraded() { " DEGRADED" } else { "" },
+                        raid.action.as_ref().map(|a| ", ".to_owned() + a).unwrap_or_default());
+                    let state = if raid.is_degraded() { span(state).color(color!(0xff0000)) } else { span(state) };
+                    rich_text([name, state])
+                }
+                None => rich_text([name]),
+            };
+            lines.push(column![line, text(attributes.join(", ")).size(12)].padding(iced::padding::left(20 * depth as u16)).into());
+        });
+    }
+    if block.disks.is_empty() {
+        lines.push(text(format!("no block devices found in {}", blockdev::SYS_BLOCK)).into());
+    }
+    column![title, iced::widget::Column::with_children(lines).spacing(6)].spacing(30)
+        .padding(10)
+        .max_width(900)
+}
+
+/// One card per interface with its link state, addresses and counters;
+/// bond and bridge ports name their master.
+fn interface_cards<'a>(network: &structs::NetworkData) -> Element<'a, Message> {
+    network.data_network.iter().fold(row![].spacing(10), |cards, interface| {
+        let link = &interface.link;
+        let state = span(link.operstate.clone()).color(if link.is_up() { color!(0x40a040) } else { color!(0xd04040) });
+        let mut details: Vec<String> = vec![
+            format!("speed: {}{}", link.speed.map(|s| format!("{} Mbit/s", s)).unwrap_or("-".to_owned()),
+                link.duplex.as_ref().map(|d| ", ".to_owned() + d + " duplex").unwrap_or_default()),
+            format!("carrier: {}", match link.carrier { Some(true) => "yes", Some(false) => "no", None => "-" }),
+            format!("driver: {}", link.driver.clone().unwrap_or("-".to_owned())),
+            format!("mac: {}  mtu: {}", interface.network_mac_address, interface.mtu),
+        ];
+        details.extend(link.master.as_ref().map(|master| format!("master: {}", master)));
+        if !link.slaves.is_empty() {
+            details.push(format!("ports: {}", link.slaves.join(", ")));
+        }
+        if !link.lower.is_empty() {
+            details.push(format!("on: {}", link.lower.join(", ")));
+        }
+        details.extend(interface.addresses.iter().map(|address| address.to_string()));
+        details.push(format!("rx: {} in {} packets, {} errors", format_bytes(interface.total_received as f64),
+            interface.total_packets_received, interface.total_errors_on_received));
+        details.push(format!("tx: {} in {} packets, {} errors", format_bytes(interface.total_transmitted as f64),
+            interface.total_packets_transmitted, interface.total_errors_on_transmitted));
+
+        let card = details.into_iter().fold(column![
+            rich_text([
+                span(interface.interface_name.clone()).font(Font { weight: font::Weight::Bold, ..Font::default() }),
+                span(format!("  {}  ", link.kind)),
+                state,
+            ]),
+        ].spacing(3), |card, line| card.push(text(line).size(13)));
+        cards.push(container(card).width(280).padding(10).style(style::card_style))
+    }).wrap().into()
+}
+
+const WIRELESS_COLORS: [iced::Color; 2] = [color!(0x4070d0), color!(0x40a040)];
+
+/// Signal and link quality of every Wi-Fi interface with their history.
+fn wireless_links<'a>(host: &Host) -> iced::widget::Column<'a, Message> {
+    host.data().wireless_data.links.iter().fold(column![].spacing(20), |links, link| {
+        let history = |value: fn(&wireless::WirelessLink) -> f32| -> Vec<f32> {
+            host.history.iter()
+                .map(|sample| sample.data.wireless_data.link(&link.interface).map(value).unwrap_or(0.0))
+                .collect()
+        };
+        let series = vec![
+            Series { color: WIRELESS_COLORS[0], values: history(wireless::WirelessLink::signal_percent) },
+            Series { color: WIRELESS_COLORS[1], values: history(wireless::WirelessLink::quality_percent) },
+        ];
+        let optional = |value: Option<f32>, unit: &str| value.map(|v| format!("{} {}", v, unit)).unwrap_or("-".to_owned());
+        links.push(column![
+            rich_text([
+                span(link.interface.clone()).font(Font { weight: font::Weight::Bold, ..Font::default() }),
+                span(link.ssid.as_ref().map(|s| format!("  \"{}\"", s)).unwrap_or_default()),
+            ]),
+            text(format!("signal: {} dBm ({:.0}%), noise: {}, link quality: {} ({:.0}%)", link.signal, link.signal_percent(),
+                optional(link.noise, "dBm"), link.link_quality, link.quality_percent())),
+            text(format!("frequency: {}, bitrate rx {} / tx {}", optional(link.frequency, "MHz"),
+                optional(link.rx_bitrate, "Mbit/s"), optional(link.tx_bitrate, "Mbit/s"))),
+            row![
+                rich_text([span("■ ").color(WIRELESS_COLORS[0]), span("signal %")]),
+                rich_text([span("■ ").color(WIRELESS_COLORS[1]), span("link quality %")]),
+            ].spacing(10),
+            LineChart::new(series, Some(100.0), host::HISTORY_LEN).view(100),
+        ].spacing(5))
+    })
+}
+
+fn routes_view<'a>(routing: &routes::RoutingData) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Routes & DNS").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let section = |name: &str| text(name.to_owned()).font(Font { weight: font::Weight::Bold, ..Font::default() });
+    let lines = |header: &str, lines: Vec<String>| {
+        let lines = if lines.is_empty() { vec!["-".to_owned()] } else { lines };
+        lines.into_iter().fold(column![section(header)].spacing(3), |column, line| column.push(text(line).size(13)))
+    };
+
+    let gateways: Vec<String> = routing.default_gateways().map(|r| r.to_string()).collect();
+    let route_row = |route: &routes::Route| {
+        let destination = if route.is_default() { "default".to_owned() } else { format!("{}/{}", route.destination, route.prefix) };
+        let cell = |value: String, width: u16| text(value).width(width).size(13);
+        row![
+            cell(destination, 260),
+            cell(route.gateway.map(|g| g.to_string()).unwrap_or("-".to_owned()), 220),
+            cell(route.interface.clone(), 90),
+            cell(route.metric.to_string(), 70),
+            cell(if route.up { "up" } else { "down" }.to_owned(), 50),
+        ].spacing(10)
+    };
+    let header = row![
+        text("destination").width(260), text("gateway").width(220), text("interface").width(90),
+        text("metric").width(70), text("state").width(50),
+    ].spacing(10);
+    let table = routing.routes.iter().fold(column![section("routes"), header].spacing(3), |table, route| table.push(route_row(route)));
+
+    let resolver = &routing.resolver;
+    column![
+        title,
+        lines("default gateways", gateways),
+        table,
+        lines("neighbours (ARP)", routing.arp.iter().map(|entry| format!("{} at {} on {}{}", entry.address, entry.mac, entry.interface,
+            if entry.complete { "" } else { " (incomplete)" })).collect()),
+        lines("nameservers", resolver.nameservers.clone()),
+        lines("search domains", resolver.search.clone()),
+        lines("resolver options", resolver.options.clone()),
+        lines("/etc/hosts", routing.hosts.iter().map(|entry| format!("{}  {}", entry.address, entry.names.join(" "))).collect()),
+    ].spacing(20)
+     .padding(10)
+     .max_width(900)
+}
+
+/// Who is logged in (utmp) and the latest logins and reboots (wtmp). Times
+/// are shown relative to the sample, so they read the same for remote hosts.
+fn sessions_view<'a>(host: &Host) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Sessions").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let data = &host.data().sessions_data;
+    if !data.available {
+        return column![title, text(format!("no utmp at {}, logins are not recorded there on this system", sessions::UTMP))].spacing(30).padding(10);
+    }
+    let now = host.latest().map(|s| s.timestamp_ms / 1000).unwrap_or(0);
+    let ago = |time: u64| format_age(now.saturating_sub(time)) + " ago";
+    let cell = |value: String, width: u16| text(value).width(width).size(13);
+
+    let current = data.sessions.iter().fold(column![
+        text(format!("{} logged in", data.sessions.len())),
+        row![
+            text("user").width(120), text("tty").width(90), text("from").width(220),
+            text("login").width(140), text("idle").width(100),
+        ].spacing(10),
+    ].spacing(3), |table, session| {
+        table.push(row![
+            cell(session.user.clone(), 120),
+            cell(session.tty.clone(), 90),
+            cell(if session.host.is_empty() { "local".to_owned() } else { session.host.clone() }, 220),
+            cell(ago(session.login_time), 140),
+            cell(session.idle_secs.map(format_age).unwrap_or("-".to_owned()), 100),
+        ].spacing(10))
+    });
+
+    let history = data.history.iter().fold(column![
+        text("recent logins and reboots"),
+        row![
+            text("user").width(120), text("tty").width(90), text("from").width(220),
+            text("login").width(140), text("duration").width(140),
+        ].spacing(10),
+    ].spacing(3), |table, login| {
+        let duration = match login.logout_time {
+            Some(logout) => format_age(logout.saturating_sub(login.login_time)),
+            None if login.kind == sessions::LoginKind::Reboot => "running".to_owned(),
+            None => "still logged in".to_owned(),
+        };
+        let user = cell(login.user.clone(), 120);
+        table.push(row![
+            if login.kind == sessions::LoginKind::Reboot { user.color(color!(0xe08000)) } else { user },
+            cell(login.tty.clone(), 90),
+            cell(login.host.clone(), 220),
+            cell(ago(login.login_time), 140),
+            cell(duration, 140),
+        ].spacing(10))
+    });
+
+    column![title, current, history].spacing(30)
+        .padding(10)
+        .max_width(900)
+}
+
+/// The stacked memory bar of the RAM pane followed by the remaining
+/// `/proc/meminfo` counters.
+fn meminfo_breakdown<'a>(info: &structs::MemInfo) -> iced::widget::Column<'a, Message> {
+    if info.mem_total == 0 {
+        return column![];
+    }
+    let segments = [
+        ("used", info.used().saturating_sub(info.shmem), color!(0xd04040)),
+        ("shared", info.shmem, color!(0xe08030)),
+        ("buffers", info.buffers, color!(0x4070d0)),
+        ("page cache", info.page_cache(), color!(0x40a0e0)),
+        ("reclaimable slab", info.slab_reclaimable, color!(0x80c0e0)),
+        ("free", info.mem_free, color!(0x60c060)),
+    ];
+    let bar = stacked_bar(segments.iter().map(|&(_, bytes, fill)| (bytes as f64, fill)), 24);
+    let legend = segments.iter().fold(column![].spacing(2), |legend, &(label, bytes, fill)| {
+        legend.push(rich_text([
+            span("■ ").color(fill),
+            span(format!("{}: {}", label, format_bytes(bytes as f64))),
+        ]))
+    });
+    let line = |label: &str, bytes: u64| text(format!("{}: {}", label, format_bytes(bytes as f64)));
+    column![
+        text(format!("{} of the {} not free is reclaimable cache (page cache, buffers, reclaimable slab)",
+            format_bytes(info.reclaimable() as f64),
+            format_bytes(info.mem_total.saturating_sub(info.mem_free) as f64))),
+        bar,
+        legend,
+        line("slab unreclaimable", info.slab_unreclaimable),
+        line("dirty", info.dirty),
+        line("writeback", info.writeback),
+        line("anon", info.anon_pages),
+        line("mapped", info.mapped),
+        line("page tables", info.page_tables),
+        text(format!("huge pages: {} free of {} ({} each)", info.huge_pages_free, info.huge_pages_total, format_bytes(info.huge_page_size as f64))),
+        text(format!("committed: {} of {} limit", format_bytes(info.committed_as as f64), format_bytes(info.commit_limit as f64))),
+    ].spacing(5)
+     .padding([20, 0])
+}
+
+const PSI_COLORS: [(&str, iced::Color); 3] = [
+    ("cpu", color!(0xd04040)),
+    ("memory", color!(0x4070d0)),
+    ("io", color!(0x40a040)),
+];
+
+fn pressure_view<'a>(host: &Host) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Pressure").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let data = host.data();
+    let resources = |p: &pressure::PressureData| [p.cpu.clone(), p.memory.clone(), p.io.clone()];
+    if resources(&data.pressure_data).iter().all(|p| p.is_none()) {
+        return column![title, text("no pressure stall information (needs Linux 4.20+ with PSI enabled)")].spacing(30).padding(10);
+    }
+
+    let avg = |value: Option<f64>| -> iced::widget::Text<'a> {
+        match value {
+            Some(v) => {
+                let cell = text(format!("{:.2}", v)).width(70);
+                match psi_severity(v) {
+                    Some(Severity::Critical) => cell.color(color!(0xff0000)),
+                    Some(Severity::Warning) => cell.color(color!(0xe08000)),
+                    None => cell,
+                }
+            }
+            None => text("-").width(70),
+        }
+    };
+    let header = row![
+        text("").width(80),
+        text("some 10s").width(70), text("some 60s").width(70), text("some 300s").width(70),
+        text("full 10s").width(70), text("full 60s").width(70), text("full 300s").width(70),
+    ].spacing(10);
+    let table = PSI_COLORS.iter().zip(resources(&data.pressure_data)).fold(column![header].spacing(4), |table, ((name, _), psi)| {
+        let Some(psi) = psi else { return table };
+        let full = psi.full.as_ref();
+        table.push(row![
+            text(name.to_string()).width(80),
+            avg(Some(psi.some.avg10)), avg(Some(psi.some.avg60)), avg(Some(psi.some.avg300)),
+            avg(full.map(|f| f.avg10)), avg(full.map(|f| f.avg60)), avg(full.map(|f| f.avg300)),
+        ].spacing(10))
+    });
+
+    // The stall rate is the growth of the `some` total between two samples,
+    // as a share of the wall-clock time between them.
+    let series: Vec<Series> = PSI_COLORS.iter().enumerate().map(|(i, (_, color))| {
+        let values = host.history.iter().zip(host.history.iter().skip(1)).map(|(prev, cur)| {
+            let elapsed_usec = cur.timestamp_ms.saturating_sub(prev.timestamp_ms) as f64 * 1000.0;
+            let total = |sample: &Sample| resources(&sample.data.pressure_data)[i].as_ref().map(|p| p.some.total).unwrap_or(0);
+            if elapsed_usec > 0.0 {
+                (total(cur).saturating_sub(total(prev)) as f64 * 100.0 / elapsed_usec) as f32
+            } else {
+                0.0
+            }
+        }).collect();
+        Series { color: *color, values }
+    }).collect();
+    let legend = PSI_COLORS.iter().fold(row![text("stall time rate (some), %:")].spacing(10), |legend, (name, color)| {
+        legend.push(rich_text([span("■ ").color(*color), span(name.to_string())]))
+    });
+
+    let mut cgroups: Vec<(String, f64, f64, f64)> = Vec::new();
+    if let Some(root) = &data.cgroup_data.root {
+        root.walk(&mut |node| {
+            let [cpu, memory, io] = resources(&node.pressure).map(|p| p.map(|p| p.some.avg10).unwrap_or(0.0));
+            if node.path != "/" && (cpu > 0.0 || memory > 0.0 || io > 0.0) {
+                cgroups.push((node.path.clone(), cpu, memory, io));
+            }
+        });
+    }
+    cgroups.sort_by(|a, b| b.1.max(b.2).max(b.3).total_cmp(&a.1.max(a.2).max(a.3)));
+    let top = cgroups.iter().take(10).fold(column![
+        text("cgroups under pressure (some avg10 cpu / memory / io)"),
+    ].spacing(4), |top, (path, cpu, memory, io)| {
+        top.push(row![text(path.clone()).width(400), avg(Some(*cpu)), avg(Some(*memory)), avg(Some(*io))].spacing(10))
+    });
+
+    column![
+        title,
+        table,
+        legend,
+        LineChart::new(series, None, host::HISTORY_LEN - 1).view(120),
+        top,
+    ].spacing(20)
+     .padding(10)
+     .max_width(900)
+}
+
+/// How many IRQ and softirq sources the kernel pane lists, busiest first.
+const KERNEL_TOP_SOURCES: usize = 20;
+
+fn kernel_view<'a>(host: &Host) -> iced::widget::Column<'a, Message> {
+    let title = rich_text([span("Kernel").color(color!(0xff0000)).font(Font { weight: font::Weight::Bold, ..Font::default() }),]);
+    let (Some(prev), Some(cur)) = (host.previous(), host.latest()) else {
+        return column![title, text("waiting for a second sample to compute rates")].spacing(30).padding(10);
+    };
+    let seconds = cur.timestamp_ms.saturating_sub(prev.timestamp_ms) as f64 / 1000.0;
+    if seconds <= 0.0 {
+        return column![title, text("waiting for a second sample to compute rates")].spacing(30).padding(10);
+    }
+    let (before, now) = (&prev.data.kernel_data, &cur.data.kernel_data);
+    let rate = |now: u64, before: u64| now.saturating_sub(before) as f64 / seconds;
+
+    // Per-source rates per CPU; sources are matched by name since IRQ lines
+    // can appear or vanish between samples when devices come and go.
+    let rates = |now: &[kernel::IrqCounter], before: &[kernel::IrqCounter]| -> Vec<(String, String, Vec<f64>)> {
+        now.iter().map(|counter| {
+            let old = before.iter().find(|b| b.name == counter.name);
+            let per_cpu = counter.per_cpu.iter().enumerate()
+                .map(|(i, count)| rate(*count, old.and_then(|o| o.per_cpu.get(i).copied()).unwrap_or(*count)))
+                .collect();
+            (counter.name.clone(), counter.description.clone(), per_cpu)
+        }).collect()
+    };
+    let irqs = rates(&now.irqs, &before.irqs);
+    let softirqs = rates(&now.softirqs, &before.softirqs);
+
+    let cpus = now.cpu_count();
+    let per_cpu_sum = |sources: &[(String, String, Vec<f64>)]| -> Vec<f64> {
+        (0..cpus).map(|i| sources.iter().filter_map(|(_, _, r)| r.get(i)).sum()).collect()
+    };
+    let cell = |value: String| text(value).width(70).size(13);
+    let cpu_header = || (0..cpus).fold(row![text("").width(90).size(13)].spacing(5), |header, i| header.push(cell(format!("cpu{}", i))));
+    let per_cpu_row = |label: &str, values: &[f64]| {
+        values.iter().fold(row![text(label.to_owned()).width(90).size(13)].spacing(5), |row, v| row.push(cell(format!("{:.0}", v))))
+    };
+
+    let sources = |title: &str, mut sources: Vec<(String, String, Vec<f64>)>| {
+        sources.sort_by(|a, b| b.2.iter().sum::<f64>().total_cmp(&a.2.iter().sum::<f64>()));
+        sources.iter().take(KERNEL_TOP_SOURCES).fold(column![text(title.to_owned()), cpu_header()].spacing(4), |table, (name, description, per_cpu)| {
+            table.push(column![
+                per_cpu_row(name, per_cpu),
+                text(format!("{:.0}/s {}", per_cpu.iter().sum::<f64>(), description)).size(12),
+            ])
+        })
+    };
+
+    column![
+        title,
+        text(format!("context switches: {:.0}/s", rate(now.context_switches, before.context_switches))),
+        text(format!("interrupts: {:.0}/s", rate(now.interrupts, before.interrupts))),
+        text(format!("forks: {:.1}/s", rate(now.forks, before.forks))),
+        text(format!("running / blocked tasks: {} / {}", now.procs_running, now.procs_blocked)),
+        column![
+            text("per CPU, events per second:"),
+            cpu_header(),
+            per_cpu_row("interrupts", &per_cpu_sum(&irqs)),
+            per_cpu_row("softirqs", &per_cpu_sum(&softirqs)),
+        ].spacing(4),
+        sources("busiest interrupt sources, per second:", irqs),
+        sources("softirqs, per second:", softirqs),
+    ].spacing(20)
+     .padding(10)
+}
+
+/// `3d 04:05` or `04:05` (hours and minutes).
+fn format_age(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {:02}:{:02}", days, hours, minutes)
+    } else {
+        format!("{:02}:{:02}", hours, minutes)
+    }
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) => format_bytes(rate) + "/s",
+        None => "-".to_owned(),
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
 mod style {
     use iced::widget::container;
     use iced::{Border, Theme};
@@ -224,4 +2084,124 @@ mod style {
             ..Default::default()
         }
     }
-}
\ No newline at end of file
+
+    pub fn card_style(theme: &Theme) -> container::Style {
+        let palette = theme.extended_palette();
+        container::Style {
+            background: Some(palette.background.base.color.into()),
+            border: Border {
+                width: 1.0,
+                radius: 4.0.into(),
+                color: palette.background.strong.color,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goes_back_and_forward_through_views() {
+        let mut pane = Pane::new(TypeData::Empty, 0);
+        pane.show(TypeData::Cpu);
+        pane.show(TypeData::Ram);
+        pane.back();
+        assert_eq!(pane.type_data, TypeData::Cpu);
+        pane.back();
+        assert_eq!(pane.type_data, TypeData::Empty);
+        pane.back();
+        assert_eq!(pane.type_data, TypeData::Empty);
+        pane.forward();
+        pane.forward();
+        assert_eq!(pane.type_data, TypeData::Ram);
+        assert!(pane.forward.is_empty());
+        assert_eq!(pane.back, [TypeData::Empty, TypeData::Cpu]);
+    }
+
+    #[test]
+    fn showing_a_view_after_going_back_drops_the_forward_views() {
+        let mut pane = Pane::new(TypeData::Empty, 0);
+        pane.show(TypeData::Cpu);
+        pane.show(TypeData::Ram);
+        pane.back();
+        pane.show(TypeData::Network);
+        assert!(pane.forward.is_empty());
+        pane.forward();
+        assert_eq!(pane.type_data, TypeData::Network);
+        pane.back();
+        assert_eq!(pane.type_data, TypeData::Cpu);
+    }
+
+    #[test]
+    fn forgets_the_oldest_view_past_the_history_limit() {
+        let mut pane = Pane::new(TypeData::Empty, 0);
+        for i in 0..=VIEW_HISTORY {
+            pane.show(if i % 2 == 0 { TypeData::Cpu } else { TypeData::Ram });
+        }
+        assert_eq!(pane.back.len(), VIEW_HISTORY);
+        for _ in 0..VIEW_HISTORY {
+            pane.back();
+        }
+        // the starting view was dropped, so the first shown one is the oldest
+        assert_eq!(pane.type_data, TypeData::Cpu);
+        assert!(pane.back.is_empty());
+    }
+
+    #[test]
+    fn opening_beside_keeps_the_other_panes() {
+        let mut container = ViewContainer::default();
+        let first = *container.panes.iter().next().unwrap().0;
+        for view in [TypeData::Cpu, TypeData::Ram, TypeData::Network] {
+            let _ = container.update(Message::Data(first, view));
+        }
+        assert_eq!(container.panes.len(), 4);
+        assert_eq!(container.count, 4);
+    }
+
+    #[test]
+    fn docking_keeps_the_main_panes() {
+        let mut container = ViewContainer::default();
+        let first = *container.panes.iter().next().unwrap().0;
+        container.open(first, Pane::new(TypeData::Cpu, 0));
+        assert_eq!(container.panes.len(), 2);
+
+        let (mut panes, torn) = pane_grid::State::new(Pane::new(TypeData::Ram, 0));
+        panes.split(pane_grid::Axis::Vertical, torn, Pane::new(TypeData::Network, 0));
+        container.dock(Detached { panes, count: 2, closing: true, origin: Some(first) });
+
+        assert_eq!(container.panes.len(), 4);
+        assert_eq!(container.count, 4);
+        let shown: Vec<TypeData> = container.panes.iter().map(|(_, pane)| pane.type_data).collect();
+        for type_data in [TypeData::Empty, TypeData::Cpu, TypeData::Ram, TypeData::Network] {
+            assert!(shown.contains(&type_data), "{:?} is gone", type_data);
+        }
+    }
+
+    #[test]
+    fn panes_torn_from_a_detached_window_dock_beside_the_oldest_pane() {
+        let mut container = ViewContainer::default();
+        let first = *container.panes.iter().next().unwrap().0;
+        container.open(first, Pane::new(TypeData::Cpu, 0));
+
+        // the window's pane ids overlap those of the main grid
+        let (mut panes, torn) = pane_grid::State::new(Pane::new(TypeData::Ram, 0));
+        let (sibling, _) = panes.split(pane_grid::Axis::Vertical, torn, Pane::new(TypeData::Network, 0)).unwrap();
+        let (gpu, _) = panes.split(pane_grid::Axis::Vertical, sibling, Pane::new(TypeData::Gpu, 0)).unwrap();
+        assert!(container.panes.get(sibling).is_some());
+        let window = window::Id::unique();
+        container.detached.insert(window, Detached { panes, count: 3, closing: false, origin: Some(first) });
+
+        let _ = container.update(Message::Window(window, Box::new(Message::Detach(gpu))));
+        let torn_window = *container.detached.keys().find(|id| **id != window).unwrap();
+        assert_eq!(container.detached[&torn_window].origin, None);
+        assert_eq!(container.detached[&window].panes.len(), 2);
+
+        let _ = container.update(Message::Dock(torn_window));
+        let docked = container.panes.iter().find(|(_, pane)| pane.type_data == TypeData::Gpu).map(|(id, _)| *id);
+        assert_eq!(container.panes.len(), 3);
+        assert_eq!(container.panes.adjacent(first, pane_grid::Direction::Right), docked);
+    }
+}
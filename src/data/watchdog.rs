@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::blockdev::BlockData;
+use super::cgroup::CgroupData;
+use super::hwmon::HwmonData;
+use super::kernel::KernelData;
+use super::pressure::PressureData;
+use super::process::ProcessesData;
+use super::routes::RoutingData;
+use super::sessions::SessionsData;
+use super::sockets::SocketsData;
+use super::structs::{AllData, ComponentsData, CpuData, DramData, GpuData, NetworkData, OperationSystem, RamData};
+use super::wireless::WirelessData;
+
+/// How long a sample waits for a collector unless it has its own timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Creating a Vulkan instance loads every installed driver.
+pub const GPU_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a failed collector rests before it is tried again; the rest
+/// doubles with every failure in a row, up to `MAX_RETRY_DELAY`.
+pub const RETRY_DELAY: Duration = Duration::from_secs(5);
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// A collector that didn't deliver in time or failed. Its part of the
+/// sample is the last value it did deliver.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Degraded {
+    pub collector: String,
+    pub error: String,
+}
+
+/// What a collector thread sends back: its result, ready to be stored.
+pub type Update = Box<dyn FnOnce(&mut AllData) + Send>;
+
+/// A collector's result, or why it has none.
+pub type Collected = Result<Update, String>;
+
+/// One part of a sample, read on a thread of its own.
+pub struct Collector {
+    pub name: &'static str,
+    /// How long a sample waits for it after starting it.
+    pub timeout: Duration,
+    collect: Arc<dyn Fn() -> Collected + Send + Sync>,
+}
+
+impl Collector {
+
+    pub fn new(name: &'static str, timeout: Duration, collect: impl Fn() -> Collected + Send + Sync + 'static) -> Collector {
+        Collector { name, timeout, collect: Arc::new(collect) }
+    }
+}
+
+macro_rules! collector {
+    ($name:literal, $field:ident, $new:expr) => {
+        collector!($name, $field, $new, DEFAULT_TIMEOUT)
+    };
+    ($name:literal, $field:ident, $new:expr, $timeout:expr) => {
+        Collector::new($name, $timeout, || {
+            let value = $new;
+            Ok(Box::new(move |data: &mut AllData| data.$field = value))
+        })
+    };
+}
+
+/// Every part of `AllData`.
+pub fn collectors() -> Vec<Collector> {
+    vec![
+        collector!("cpu", cpu_data, CpuData::new()),
+        Collector::new("gpu", GPU_TIMEOUT, || {
+            let value = GpuData::new()?;
+            Ok(Box::new(move |data: &mut AllData| data.gpu_data = value))
+        }),
+        collector!("disk", dram_data, DramData::new()),
+        collector!("ram", ram_data, RamData::new()),
+        collector!("os", os_data, OperationSystem::new()),
+        collector!("network", network_data, NetworkData::new()),
+        collector!("components", components_data, ComponentsData::new()),
+        collector!("cgroups", cgroup_data, CgroupData::new()),
+        collector!("sockets", socket_data, SocketsData::new()),
+        collector!("hwmon", hwmon_data, HwmonData::new()),
+        collector!("pressure", pressure_data, PressureData::new()),
+        collector!("kernel", kernel_data, KernelData::new()),
+        collector!("blocks", block_data, BlockData::new()),
+        collector!("wireless", wireless_data, WirelessData::new()),
+        collector!("routes", routing_data, RoutingData::new()),
+        collector!("sessions", sessions_data, SessionsData::new()),
+        collector!("processes", processes_data, ProcessesData::new()),
+    ]
+}
+
+/// A collector thread that hasn't answered yet.
+struct Running {
+    receiver: Receiver<Collected>,
+    started: Instant,
+}
+
+impl Running {
+
+    fn start(collector: &Collector) -> Running {
+        let (sender, receiver) = mpsc::channel();
+        let collect = Arc::clone(&collector.collect);
+        thread::spawn(move || {
+            let _ = sender.send(collect());
+        });
+        Running { receiver, started: Instant::now() }
+    }
+}
+
+/// A collector that failed, and when it may be tried again.
+struct Failure {
+    error: String,
+    in_a_row: u32,
+    retry: Instant,
+}
+
+/// Takes samples with every collector on a thread of its own, so a stale
+/// NFS mount or a wedged driver only holds up its own part of the data.
+pub struct Watchdog {
+    collectors: Vec<Collector>,
+    running: HashMap<&'static str, Running>,
+    failed: HashMap<&'static str, Failure>,
+    last: AllData,
+}
+
+impl Watchdog {
+
+    pub fn new() -> Watchdog {
+        Watchdog::with_collectors(collectors())
+    }
+
+    pub fn with_collectors(collectors: Vec<Collector>) -> Watchdog {
+        Watchdog { collectors, running: HashMap::new(), failed: HashMap::new(), last: AllData::default() }
+    }
+
+    /// Starts the collectors and waits for each until its timeout has passed
+    /// since the start; they run side by side, so a sample takes at most the
+    /// longest timeout. One still running from an earlier sample isn't
+    /// waited for again nor started a second time, so a hung read doesn't
+    /// pile up threads; its result is taken whenever it finally arrives.
+    /// One that failed rests for a while before it is started again.
+    pub fn collect(&mut self) -> AllData {
+        let round = Instant::now();
+        for collector in &self.collectors {
+            if self.failed.get(collector.name).is_some_and(|f| f.retry > round) {
+                continue;
+            }
+            self.running.entry(collector.name).or_insert_with(|| Running::start(collector));
+        }
+
+        let mut data = std::mem::take(&mut self.last);
+        let mut degraded: Vec<Degraded> = Vec::new();
+        for collector in &self.collectors {
+            let Some(running) = self.running.remove(collector.name) else {
+                if let Some(failure) = self.failed.get(collector.name) {
+                    degraded.push(Degraded { collector: collector.name.to_owned(), error: failure.error.clone() });
+                }
+                continue;
+            };
+            let wait = if running.started >= round {
+                (round + collector.timeout).saturating_duration_since(Instant::now())
+            } else {
+                Duration::ZERO
+            };
+            let error = match running.receiver.recv_timeout(wait) {
+                Ok(Ok(update)) => {
+                    update(&mut data);
+                    self.failed.remove(collector.name);
+                    continue;
+                }
+                Ok(Err(error)) => error,
+                Err(RecvTimeoutError::Timeout) => {
+                    degraded.push(Degraded {
+                        collector: collector.name.to_owned(),
+                        error: format!("no answer for {} s", running.started.elapsed().as_secs()),
+                    });
+                    self.running.insert(collector.name, running);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => "the collector failed".to_owned(),
+            };
+            let in_a_row = self.failed.get(collector.name).map_or(1, |f| f.in_a_row + 1);
+            let delay = RETRY_DELAY.saturating_mul(1 << (in_a_row - 1).min(16)).min(MAX_RETRY_DELAY);
+            degraded.push(Degraded { collector: collector.name.to_owned(), error: error.clone() });
+            self.failed.insert(collector.name, Failure { error, in_a_row, retry: Instant::now() + delay });
+        }
+        self.last = data.clone();
+        data.degraded = degraded;
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// A collector counting its calls into `os_data.uptime`.
+    fn counting() -> Collector {
+        let calls = Arc::new(AtomicU64::new(0));
+        Collector::new("fast", TIMEOUT, move || {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Box::new(move |data: &mut AllData| data.os_data.uptime = n))
+        })
+    }
+
+    /// A collector that blocks until it is released, then stores its call
+    /// number in `ram_data.total_memory`.
+    fn blocking(calls: Arc<AtomicU64>, release: Arc<Mutex<mpsc::Receiver<()>>>) -> Collector {
+        Collector::new("slow", TIMEOUT, move || {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let _ = release.lock().unwrap().recv();
+            Ok(Box::new(move |data: &mut AllData| data.ram_data.total_memory = n))
+        })
+    }
+
+    fn degraded(data: &AllData) -> Vec<&str> {
+        data.degraded.iter().map(|d| d.collector.as_str()).collect()
+    }
+
+    #[test]
+    fn hung_collector_keeps_its_last_value() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let (release, released) = mpsc::channel();
+        let mut watchdog = Watchdog::with_collectors(vec![counting(), blocking(Arc::clone(&calls), Arc::new(Mutex::new(released)))]);
+
+        release.send(()).unwrap();
+        let data = watchdog.collect();
+        assert!(data.degraded.is_empty());
+        assert_eq!((data.os_data.uptime, data.ram_data.total_memory), (1, 1));
+
+        // hangs now: reported, with the value of the first sample
+        let data = watchdog.collect();
+        assert_eq!(degraded(&data), ["slow"]);
+        assert_eq!((data.os_data.uptime, data.ram_data.total_memory), (2, 1));
+
+        // still running: not started a second time, not waited for
+        let started = Instant::now();
+        let data = watchdog.collect();
+        assert!(started.elapsed() < TIMEOUT);
+        assert_eq!(degraded(&data), ["slow"]);
+        assert_eq!((data.os_data.uptime, data.ram_data.total_memory), (3, 1));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // the late result is taken by the next sample
+        release.send(()).unwrap();
+        thread::sleep(TIMEOUT);
+        let data = watchdog.collect();
+        assert!(data.degraded.is_empty());
+        assert_eq!((data.os_data.uptime, data.ram_data.total_memory), (4, 2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // and then it runs again
+        release.send(()).unwrap();
+        let data = watchdog.collect();
+        assert!(data.degraded.is_empty());
+        assert_eq!(data.ram_data.total_memory, 3);
+    }
+
+    #[test]
+    fn failed_collector_is_degraded() {
+        // long enough for the panic to unwind first, however busy the machine
+        let panicking = Collector::new("broken", Duration::from_secs(10), || panic!("collector test panic"));
+        let mut watchdog = Watchdog::with_collectors(vec![counting(), panicking]);
+        let data = watchdog.collect();
+        assert_eq!(data.degraded, [Degraded { collector: "broken".to_owned(), error: "the collector failed".to_owned() }]);
+        assert_eq!(data.os_data.uptime, 1);
+    }
+
+    #[test]
+    fn failing_collector_rests_before_a_retry() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&calls);
+        let failing = Collector::new("gpu", TIMEOUT, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err("no Vulkan backend".to_owned())
+        });
+        let mut watchdog = Watchdog::with_collectors(vec![counting(), failing]);
+        for _ in 0..3 {
+            let data = watchdog.collect();
+            assert_eq!(data.degraded, [Degraded { collector: "gpu".to_owned(), error: "no Vulkan backend".to_owned() }]);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
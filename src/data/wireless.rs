@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+pub const PROC_NET_WIRELESS: &str = "/proc/net/wireless";
+
+/// The link quality most drivers report as 100%.
+pub const LINK_QUALITY_MAX: f32 = 70.0;
+
+/// How long the `iw dev <name> link` answer of an interface is reused. SSID
+/// and frequency only change on a reconnect and the bitrates are only a
+/// rough figure, so there's no point in spawning `iw` on every sample.
+pub const IW_REFRESH: Duration = Duration::from_secs(30);
+
+static IW: LazyLock<Mutex<IwCache>> = LazyLock::new(|| Mutex::new(IwCache::new("iw")));
+
+/// What `iw dev <name> link` tells about a connection.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct IwLink {
+    ssid: Option<String>,
+    frequency: Option<f32>,
+    rx_bitrate: Option<f32>,
+    tx_bitrate: Option<f32>,
+}
+
+impl IwLink {
+
+    fn parse(text: &str) -> IwLink {
+        let number = |value: &str| value.split_whitespace().next().and_then(|v| v.parse::<f32>().ok());
+        let mut link = IwLink::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.trim().split_once(": ") else { continue };
+            match key {
+                "SSID" => link.ssid = Some(value.to_owned()),
+                "freq" => link.frequency = number(value),
+                "rx bitrate" => link.rx_bitrate = number(value),
+                "tx bitrate" => link.tx_bitrate = number(value),
+                _ => {}
+            }
+        }
+        link
+    }
+
+    /// `None` when `program` isn't installed.
+    fn read(program: &str, interface: &str) -> Option<IwLink> {
+        match Command::new(program).args(["dev", interface, "link"]).output() {
+            Ok(output) if output.status.success() => Some(IwLink::parse(&String::from_utf8_lossy(&output.stdout))),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            // not associated, or the interface went away
+            _ => Some(IwLink::default()),
+        }
+    }
+}
+
+struct IwCache {
+    program: String,
+    /// When `iw` turned out not to be installed; it's only looked for
+    /// again `IW_REFRESH` later.
+    missing: Option<Instant>,
+    links: HashMap<String, (Instant, IwLink)>,
+}
+
+impl IwCache {
+
+    fn new(program: &str) -> IwCache {
+        IwCache { program: program.to_owned(), missing: None, links: HashMap::new() }
+    }
+
+    /// The cached answer for `interface`, asking `iw` again once it's older
+    /// than `IW_REFRESH`.
+    fn get(&mut self, interface: &str) -> IwLink {
+        if self.missing.is_some_and(|since| since.elapsed() < IW_REFRESH) {
+            return IwLink::default();
+        }
+        if let Some((read, link)) = self.links.get(interface) {
+            if read.elapsed() < IW_REFRESH {
+                return link.clone();
+            }
+        }
+        let Some(link) = IwLink::read(&self.program, interface) else {
+            self.missing = Some(Instant::now());
+            self.links.clear();
+            return IwLink::default();
+        };
+        self.missing = None;
+        self.links.insert(interface.to_owned(), (Instant::now(), link.clone()));
+        link
+    }
+}
+
+/// The state of one Wi-Fi interface: the `/proc/net/wireless` counters and,
+/// when `iw` is installed and the interface is associated, what `iw dev
+/// <name> link` tells about the connection.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WirelessLink {
+    pub interface: String,
+    pub link_quality: f32,
+    /// dBm.
+    pub signal: f32,
+    /// dBm; drivers that don't measure it report -256.
+    pub noise: Option<f32>,
+    pub ssid: Option<String>,
+    /// MHz.
+    pub frequency: Option<f32>,
+    /// Mbit/s.
+    pub rx_bitrate: Option<f32>,
+    pub tx_bitrate: Option<f32>,
+}
+
+impl WirelessLink {
+
+    /// Parses a data line like
+    /// `wlan0: 0000   54.  -56.  -256        0      0      0      0      0        0`.
+    pub fn parse(line: &str) -> Option<WirelessLink> {
+        let (interface, rest) = line.split_once(':')?;
+        let fields: Vec<f32> = rest.split_whitespace()
+            .skip(1)
+            .take(3)
+            .filter_map(|f| f.trim_end_matches('.').parse::<f32>().ok())
+            .collect();
+        let [link_quality, signal, noise] = fields[..] else { return None };
+        Some(WirelessLink {
+            interface: interface.trim().to_owned(),
+            link_quality,
+            signal,
+            noise: Some(noise).filter(|n| *n > -256.0 && *n != 0.0),
+            ..WirelessLink::default()
+        })
+    }
+
+    /// Signal strength in % the way NetworkManager shows it: -100 dBm
+    /// and below is 0%, -50 dBm and above is 100%.
+    pub fn signal_percent(&self) -> f32 {
+        (2.0 * (self.signal + 100.0)).clamp(0.0, 100.0)
+    }
+
+    pub fn quality_percent(&self) -> f32 {
+        (self.link_quality * 100.0 / LINK_QUALITY_MAX).clamp(0.0, 100.0)
+    }
+
+    /// Fills in SSID, frequency and bitrates from `iw dev <name> link`.
+    fn set_iw(&mut self, iw: IwLink) {
+        self.ssid = iw.ssid;
+        self.frequency = iw.frequency;
+        self.rx_bitrate = iw.rx_bitrate;
+        self.tx_bitrate = iw.tx_bitrate;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WirelessData {
+    pub links: Vec<WirelessLink>,
+}
+
+impl WirelessData {
+
+    pub fn new() -> WirelessData {
+        let text = fs::read_to_string(PROC_NET_WIRELESS).unwrap_or_default();
+        // the first two lines are the table header
+        let mut links: Vec<WirelessLink> = text.lines().skip(2).filter_map(WirelessLink::parse).collect();
+        let mut iw = IW.lock().unwrap_or_else(|e| e.into_inner());
+        iw.links.retain(|interface, _| links.iter().any(|l| &l.interface == interface));
+        for link in &mut links {
+            link.set_iw(iw.get(&link.interface));
+        }
+        WirelessData { links }
+    }
+
+    pub fn link(&self, interface: &str) -> Option<&WirelessLink> {
+        self.links.iter().find(|l| l.interface == interface)
+    }
+
+    #[allow(dead_code)]
+    pub fn print_data(&self) {
+        println!("**Wireless data***");
+        for link in &self.links {
+            println!("{}............quality {} signal {} dBm noise {:?} ssid {:?} freq {:?} MHz",
+                     link.interface, link.link_quality, link.signal, link.noise, link.ssid, link.frequency);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iw_link() {
+        let text = "Connected to 3c:a6:2f:12:34:56 (on wlp2s0)
+\tSSID: home network
+\tfreq: 5180.0
+\tRX: 1530461 bytes (6720 packets)
+\tTX: 203884 bytes (1409 packets)
+\tsignal: -56 dBm
+\trx bitrate: 866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2
+\ttx bitrate: 650.0 MBit/s VHT-MCS 7 80MHz short GI VHT-NSS 2
+";
+        assert_eq!(IwLink::parse(text), IwLink {
+            ssid: Some("home network".to_owned()),
+            frequency: Some(5180.0),
+            rx_bitrate: Some(866.7),
+            tx_bitrate: Some(650.0),
+        });
+        assert_eq!(IwLink::parse("Not connected.\n"), IwLink::default());
+    }
+
+    #[test]
+    fn parses_proc_net_wireless_lines() {
+        let link = WirelessLink::parse("wlp2s0: 0000   54.  -56.  -256        0      0      0      0      0        0").unwrap();
+        assert_eq!((link.interface.as_str(), link.link_quality, link.signal, link.noise), ("wlp2s0", 54.0, -56.0, None));
+        assert_eq!((link.signal_percent(), link.quality_percent().round()), (88.0, 77.0));
+
+        let link = WirelessLink::parse(" wlan0: 0000   70.  -40.  -92.       0      0      0      0      0        0").unwrap();
+        assert_eq!((link.interface.as_str(), link.noise, link.signal_percent()), ("wlan0", Some(-92.0), 100.0));
+
+        assert_eq!(WirelessLink::parse("wlan0: 0000   54."), None);
+        assert_eq!(WirelessLink::parse(" face |tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn looks_for_a_missing_iw_again_later() {
+        let mut cache = IwCache::new("/nonexistent/iw");
+        assert_eq!(cache.get("wlan0"), IwLink::default());
+        let missing = cache.missing.expect("iw is missing");
+
+        // installed meanwhile, but not looked for before IW_REFRESH is over;
+        // `true` stands in for an `iw` telling of a link that's down
+        cache.program = "true".to_owned();
+        cache.get("wlan0");
+        assert_eq!(cache.missing, Some(missing));
+        assert!(cache.links.is_empty());
+
+        cache.missing = missing.checked_sub(IW_REFRESH);
+        assert_eq!(cache.get("wlan0"), IwLink::default());
+        assert_eq!(cache.missing, None);
+        assert!(cache.links.contains_key("wlan0"));
+    }
+}
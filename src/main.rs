@@ -1,26 +1,61 @@
+use data::options::{Mode, Options};
+use data::query;
+use data::remote;
 use data::vew_data::ViewContainer;
 use iced::{window, Theme};
 
 mod data;
 
-#[cfg(target_os = "windows")]
 pub fn main() -> iced::Result {
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if options.mode == Mode::Ps {
+        if let Err(e) = query::run_cli(&options.query) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+    if options.mode == Mode::Agent {
+        if let Err(e) = remote::run_agent(&options.listen, options.interval) {
+            eprintln!("{}: {}", options.listen, e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let container = match ViewContainer::from_options(&options) {
+        Ok(container) => container,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    iced::daemon(ViewContainer::title, ViewContainer::update, ViewContainer::view)
+    .subscription(ViewContainer::subscription)
+    .theme(|_, _| Theme::TokyoNightLight)
+        .run_with(move || {
+            let mut container = container;
+            let open = container.start(window_settings(), options.mini);
+            (container, open)
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn window_settings() -> window::Settings {
     let mut w = window::Settings::default();
     let mut ps = window::Settings::default().platform_specific;
     ps.drag_and_drop = false;
     w.platform_specific = ps;
-
-    iced::application("Monytoring System", ViewContainer::update, ViewContainer::view)
-    .theme(|_| Theme::TokyoNightLight)
-    .window(w)
-        .run()
+    w
 }
 
 #[cfg(target_os = "linux")]
-pub fn main() -> iced::Result {
-    
-
-    iced::application("Monytoring System", ViewContainer::update, ViewContainer::view)
-    .theme(|_| Theme::TokyoNightLight)
-        .run()
+fn window_settings() -> window::Settings {
+    window::Settings::default()
 }